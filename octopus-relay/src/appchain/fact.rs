@@ -14,6 +14,17 @@ pub enum RawFact {
     Burn(Burned),
 }
 
+impl RawFact {
+    /// Get the block timestamp when the fact was created
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            RawFact::ValidatorHistoryIndexSet(vh_set) => vh_set.timestamp,
+            RawFact::LockAsset(locked) => locked.timestamp.0,
+            RawFact::Burn(burned) => burned.timestamp.0,
+        }
+    }
+}
+
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct AppchainLockedAsset {
     pub sequence_number: SeqNum,
@@ -46,6 +57,7 @@ impl AppchainLockedAsset {
             sender_id: self.sender_id.clone(),
             receiver: self.receiver.clone(),
             amount: self.amount,
+            timestamp: self.timestamp.into(),
         }
     }
 }
@@ -58,6 +70,7 @@ impl AppchainBurnedNativeToken {
             sender_id: self.sender_id.clone(),
             receiver: self.receiver.clone(),
             amount: self.amount,
+            timestamp: self.timestamp.into(),
         }
     }
 }
@@ -2,7 +2,7 @@ use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{env, AccountId, Balance, BlockHeight};
 
-use crate::types::AppchainId;
+use crate::types::{AppchainId, BootingInfo, ChainSpecInfo};
 
 /// Metadata of an appchain of Octopus Network
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -40,6 +40,10 @@ pub struct AppchainMetadata {
     pub block_height: BlockHeight,
     ///
     pub subql_url: String,
+    /// Category tag of the appchain, for discovery
+    pub category: Option<String>,
+    /// Short note of operational status of the appchain
+    pub operational_note: Option<String>,
 }
 
 impl AppchainMetadata {
@@ -53,6 +57,7 @@ impl AppchainMetadata {
         commit_id: String,
         email: String,
         bond_tokens: u128,
+        category: Option<String>,
     ) -> Self {
         Self {
             id: appchain_id,
@@ -71,8 +76,36 @@ impl AppchainMetadata {
             rpc_endpoint: String::new(),
             block_height: env::block_index(),
             subql_url: String::new(),
+            category,
+            operational_note: None,
         }
     }
+    /// Return the booting info of the appchain, `None` if the appchain is not activated yet
+    pub fn to_booting_info(&self) -> Option<BootingInfo> {
+        if self.boot_nodes.is_empty() {
+            return None;
+        }
+        Some(BootingInfo {
+            boot_nodes: self.boot_nodes.clone(),
+            rpc_endpoint: self.rpc_endpoint.clone(),
+            chain_spec_url: self.chain_spec_url.clone(),
+            chain_spec_hash: self.chain_spec_hash.clone(),
+            chain_spec_raw_url: self.chain_spec_raw_url.clone(),
+            chain_spec_raw_hash: self.chain_spec_raw_hash.clone(),
+        })
+    }
+    /// Return the chain spec of the appchain, `None` if it is not set yet
+    pub fn to_chain_spec_info(&self) -> Option<ChainSpecInfo> {
+        if self.chain_spec_url.is_empty() {
+            return None;
+        }
+        Some(ChainSpecInfo {
+            url: self.chain_spec_url.clone(),
+            hash: self.chain_spec_hash.clone(),
+            raw_url: self.chain_spec_raw_url.clone(),
+            raw_hash: self.chain_spec_raw_hash.clone(),
+        })
+    }
     /// Update basic info of metadata content of current appchain
     pub fn update_basic_info(
         &mut self,
@@ -126,4 +159,12 @@ impl AppchainMetadata {
         self.subql_url.clear();
         self.subql_url.push_str(subql.as_str());
     }
+    /// Update category tag of current appchain
+    pub fn update_category(&mut self, category: Option<String>) {
+        self.category = category;
+    }
+    /// Update operational note of current appchain
+    pub fn update_operational_note(&mut self, note: Option<String>) {
+        self.operational_note = note;
+    }
 }
@@ -1,5 +1,5 @@
-mod delegator;
-mod fact;
-mod validator;
+pub mod delegator;
+pub mod fact;
 pub mod metadata;
 pub mod state;
+pub mod validator;
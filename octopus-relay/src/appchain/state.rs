@@ -1,23 +1,36 @@
-use std::convert::TryInto;
-
+use codec::Encode;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, Vector};
 use near_sdk::json_types::U128;
 use near_sdk::{env, log, AccountId, Balance, Timestamp};
 
 use crate::appchain_prover::AppchainProver;
+use crate::events::RelayEvent;
 use crate::storage_key::StorageKey;
 use crate::types::{
-    AppchainId, AppchainStatus, Burned, Fact, HistoryIndex, LiteValidator, Locked, SeqNum,
-    ValidatorId, ValidatorIndex, ValidatorSet,
+    AccountVote, AppchainId, AppchainStatus, BridgeDirections, BurnAssetPayload, Burned,
+    DelegatorId, Fact, FailedMint, FailedRemoval, FailedUnlock, GasConfig, HistoryIndex, KeyType,
+    LiteValidator, Locked, LockedAudit, RawFactView, SeqNum, SetId, ValidatorChanges, ValidatorId,
+    ValidatorIndex, ValidatorSet, Vote,
 };
-use crate::VALIDATOR_SET_CYCLE;
+use crate::DEFAULT_VALIDATOR_SET_CYCLE;
 
+use super::delegator::AppchainDelegator;
 use super::fact::{AppchainBurnedNativeToken, AppchainLockedAsset, RawFact};
 use super::validator::{
     AppchainValidator, ValidatorHistory, ValidatorHistoryIndexSet, ValidatorHistoryList,
 };
 
+/// Convert length of a collection to `SeqNum`, panics if the length doesn't fit
+pub fn seq_num_from_len(len: u64) -> SeqNum {
+    assert!(
+        len <= u64::from(SeqNum::MAX),
+        "The length {} exceeds the maximum sequence number.",
+        len
+    );
+    len as SeqNum
+}
+
 /// Appchain state of an appchain of Octopus Network
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct AppchainState {
@@ -36,10 +49,15 @@ pub struct AppchainState {
     pub validator_set_timestamp: Timestamp,
     /// Timestamp when the appchain boots
     pub booting_timestamp: Timestamp,
-    /// Collection of validators which were removed from the appchain
+    /// Timestamp when the appchain goes staging
+    pub staging_started_at: Timestamp,
+    /// Timestamp when the appchain was frozen, 0 if it isn't frozen
+    pub frozen_at: Timestamp,
+    /// Collection of validators which were removed from the appchain, no longer written
     ///
-    /// Each remove action for validator will create a new key in this collection,
-    /// for users to withdraw their tokens.
+    /// The stake of a validator is transferred to its refund account when it is removed,
+    /// there is nothing left to withdraw. The collection is kept for the layout of the stored
+    /// state and the storage migration.
     pub removed_validators: UnorderedMap<ValidatorId, LazyOption<AppchainValidator>>,
     /// History records of facts happened which were related to the appchain
     pub raw_facts: Vector<LazyOption<RawFact>>,
@@ -49,14 +67,29 @@ pub struct AppchainState {
     pub staked_balance: Balance,
     /// Collection of total amount of locked tokens
     pub total_locked_tokens: UnorderedMap<AccountId, u128>,
+    /// Collection of accumulated amount of unlocked tokens
+    pub total_unlocked_tokens: LookupMap<AccountId, u128>,
     /// Total upvote balance of OCT token of the appchain
     pub upvote_balance: Balance,
     /// Total downvote balance of OCT token of the appchain
     pub downvote_balance: Balance,
+    /// Vote amounts of each account to the appchain
+    pub account_votes: LookupMap<AccountId, AccountVote>,
     /// The cross-chain prover of the appchain
     pub prover: AppchainProver,
+    /// Trusted root of the MMR of the appchain, relayed messages are verified against it
+    pub mmr_root: Option<Vec<u8>>,
     /// used_messages of the appchain
     pub used_messages: UnorderedMap<u64, bool>,
+    /// All messages with nonce below this watermark are treated as used,
+    /// their records in `used_messages` may have been pruned
+    pub used_messages_watermark: u64,
+    /// Records of unlock messages which were skipped
+    pub failed_unlocks: Vector<FailedUnlock>,
+    /// Records of failed mints of native token, by nonce of the source message
+    pub failed_mints: UnorderedMap<u64, FailedMint>,
+    /// Removals of validators whose refund transfer was failed, the validators are still active
+    pub failed_removals: UnorderedMap<ValidatorId, FailedRemoval>,
     /// map of validator_history_list
     pub validator_history_lists: LookupMap<ValidatorIndex, LazyOption<ValidatorHistoryList>>,
     pub validator_index_to_id: LookupMap<ValidatorIndex, ValidatorId>,
@@ -64,6 +97,44 @@ pub struct AppchainState {
     pub validator_id_to_index: LookupMap<ValidatorId, ValidatorIndex>,
     /// Current validators by index
     pub validator_indexes: UnorderedMap<ValidatorIndex, bool>,
+    /// Maximum number of validators of the appchain, 0 means unlimited
+    pub max_validators: u32,
+    /// Inclusive range of decimals of bridge tokens can be permitted, `None` means unlimited
+    pub allowed_decimals: Option<(u32, u32)>,
+    /// Maximum net minted supply of the native token on NEAR, 0 means unlimited
+    pub native_mint_cap: Balance,
+    /// Net minted supply of the native token on NEAR, including the pending mints
+    pub native_minted_supply: Balance,
+    /// Accumulated amount of native token burnt on the appchain, by relayed `Lock` messages
+    pub burned_on_appchain_total: Balance,
+    /// Accumulated amount of native token minted on NEAR, including the pending mints
+    pub minted_on_near_total: Balance,
+    /// Gas for calls to token contracts in bridging of the appchain, `None` means the default
+    pub gas_override: Option<GasConfig>,
+    /// Bridging directions enabled for the appchain
+    pub bridge_directions: BridgeDirections,
+    /// Maximum ratio of delegated balance to self stake of a validator in percent,
+    /// 0 means unlimited
+    pub max_delegation_ratio: u16,
+    /// Whether facts of locking and burning assets are stored in `raw_facts`
+    ///
+    /// If not, locking and burning are only reported by events, saving storage, but the facts
+    /// can't be queried from the contract and locked tokens can't be audited or repaired.
+    /// Facts of validator sets are always stored.
+    pub persist_asset_facts: bool,
+    /// Type of the public keys used as validator ids, set at registration
+    pub validator_key_type: KeyType,
+    /// Length of a cycle of validator sets in nanoseconds, kept the same as the relay contract
+    pub validator_set_cycle: u64,
+    /// Sequence number of the next event of the appchain, increased by 1 for each emitted event
+    pub event_seq: u64,
+    /// OCT tokens which are no longer staked or delegated but not paid out yet, by owner account
+    pub unclaimed_balances: LookupMap<AccountId, Balance>,
+    /// Indexes of validators in validator sets, by the set id of the validator set
+    /// which stored them first, see `ValidatorHistoryIndexSet::indexes_set_id`
+    pub validator_index_lists: LookupMap<SetId, Vec<ValidatorIndex>>,
+    /// `indexes_set_id` of the latest validator set, `None` if no validator set is created
+    pub latest_indexes_set_id: Option<SetId>,
 }
 
 impl AppchainState {
@@ -82,6 +153,8 @@ impl AppchainState {
             validators_timestamp: 0,
             validator_set_timestamp: 0,
             booting_timestamp: 0,
+            staging_started_at: 0,
+            frozen_at: 0,
             removed_validators: UnorderedMap::new(
                 StorageKey::RemovedAppchainValidators(appchain_id.clone()).into_bytes(),
             ),
@@ -91,12 +164,29 @@ impl AppchainState {
             total_locked_tokens: UnorderedMap::new(
                 StorageKey::AppchainTotalLockedTokens(appchain_id.clone()).into_bytes(),
             ),
+            total_unlocked_tokens: LookupMap::new(
+                StorageKey::AppchainTotalUnlockedTokens(appchain_id.clone()).into_bytes(),
+            ),
             upvote_balance: 0,
             downvote_balance: 0,
+            account_votes: LookupMap::new(
+                StorageKey::AccountVotes(appchain_id.clone()).into_bytes(),
+            ),
             prover: AppchainProver,
+            mmr_root: None,
             used_messages: UnorderedMap::new(
                 StorageKey::UsedMessage(appchain_id.clone()).into_bytes(),
             ),
+            used_messages_watermark: 0,
+            failed_unlocks: Vector::new(
+                StorageKey::FailedUnlocks(appchain_id.clone()).into_bytes(),
+            ),
+            failed_mints: UnorderedMap::new(
+                StorageKey::FailedMints(appchain_id.clone()).into_bytes(),
+            ),
+            failed_removals: UnorderedMap::new(
+                StorageKey::FailedRemovals(appchain_id.clone()).into_bytes(),
+            ),
             validator_history_lists: LookupMap::new(
                 StorageKey::ValidatorHistoryLists(appchain_id.clone()).into_bytes(),
             ),
@@ -110,6 +200,26 @@ impl AppchainState {
             validator_indexes: UnorderedMap::new(
                 StorageKey::ValidatorIndexes(appchain_id.clone()).into_bytes(),
             ),
+            max_validators: 0,
+            allowed_decimals: None,
+            native_mint_cap: 0,
+            native_minted_supply: 0,
+            burned_on_appchain_total: 0,
+            minted_on_near_total: 0,
+            gas_override: None,
+            bridge_directions: BridgeDirections::default(),
+            max_delegation_ratio: 0,
+            persist_asset_facts: true,
+            validator_key_type: KeyType::default(),
+            validator_set_cycle: DEFAULT_VALIDATOR_SET_CYCLE,
+            event_seq: 0,
+            unclaimed_balances: LookupMap::new(
+                StorageKey::UnclaimedBalances(appchain_id.clone()).into_bytes(),
+            ),
+            validator_index_lists: LookupMap::new(
+                StorageKey::ValidatorIndexLists(appchain_id.clone()).into_bytes(),
+            ),
+            latest_indexes_set_id: None,
         }
     }
     /// Clear extra storage used by the appchain
@@ -126,12 +236,11 @@ impl AppchainState {
 
     /// Get all validators of the appchain
     pub fn get_validators(&self, start: u32, limit: u32) -> Vec<AppchainValidator> {
-        let indexes_len = self.validator_indexes.len();
-        let end = std::cmp::min(start + limit, indexes_len as u32);
-        let indexes: Vec<ValidatorIndex> = self.validator_indexes.keys().collect();
+        let indexes = self.validator_indexes.keys_as_vector();
+        let end = std::cmp::min(start.saturating_add(limit), indexes.len() as u32);
         let mut validators = Vec::new();
         for index in start..end {
-            let v_index = indexes.get(index as usize).unwrap();
+            let v_index = indexes.get(index as u64).unwrap();
             let v_id = self.validator_index_to_id.get(&v_index).unwrap();
             let validator = self.validators.get(&v_id).unwrap().get().unwrap();
             validators.push(validator);
@@ -156,6 +265,14 @@ impl AppchainState {
             self.validators.get(validator_id).is_none(),
             "This validator is already staked on the appchain!"
         );
+        // The record of a validator is kept while the refund of its stake is pending
+        assert!(
+            !env::storage_has_key(
+                &StorageKey::AppchainValidator(self.appchain_id.clone(), validator_id.clone())
+                    .into_bytes()
+            ),
+            "This validator is being removed from the appchain!"
+        );
         let account_exists = self.account_exists(account_id);
         assert!(
             !account_exists,
@@ -163,6 +280,67 @@ impl AppchainState {
         );
     }
 
+    /// Whether the number of validators has reached `max_validators`
+    pub fn is_validators_full(&self) -> bool {
+        self.max_validators > 0 && self.validators.len() >= self.max_validators as u64
+    }
+
+    /// Get vote amounts of an account, `None` if the account hasn't voted
+    pub fn get_account_vote(&self, account_id: &AccountId) -> Option<AccountVote> {
+        self.account_votes.get(account_id)
+    }
+
+    /// Record OCT tokens of an account voted to the appchain, the tokens are held by the contract
+    pub fn vote(&mut self, account_id: &AccountId, vote: Vote, amount: Balance) {
+        let mut account_vote = self.account_votes.get(account_id).unwrap_or(AccountVote {
+            upvote: 0.into(),
+            downvote: 0.into(),
+        });
+        match vote {
+            Vote::Yes => {
+                self.upvote_balance += amount;
+                account_vote.upvote = (account_vote.upvote.0 + amount).into();
+            }
+            Vote::No => {
+                self.downvote_balance += amount;
+                account_vote.downvote = (account_vote.downvote.0 + amount).into();
+            }
+        }
+        self.account_votes.insert(account_id, &account_vote);
+    }
+
+    /// Remove the vote record of an account for withdrawing, the vote balances are decreased
+    pub fn take_account_vote(&mut self, account_id: &AccountId) -> Option<AccountVote> {
+        let account_vote = self.account_votes.remove(account_id)?;
+        self.upvote_balance -= account_vote.upvote.0;
+        self.downvote_balance -= account_vote.downvote.0;
+        Some(account_vote)
+    }
+
+    /// Restore the vote record of an account whose withdrawal was failed
+    pub fn restore_account_vote(&mut self, account_id: &AccountId, account_vote: &AccountVote) {
+        self.upvote_balance += account_vote.upvote.0;
+        self.downvote_balance += account_vote.downvote.0;
+        self.account_votes.insert(account_id, account_vote);
+    }
+
+    /// Get the OCT tokens of an account which can be withdrawn by `withdraw_unclaimed_balance`
+    pub fn get_unclaimed_balance(&self, account_id: &AccountId) -> Balance {
+        self.unclaimed_balances.get(account_id).unwrap_or(0)
+    }
+
+    /// Record OCT tokens of an account which are no longer staked or delegated
+    pub fn add_unclaimed_balance(&mut self, account_id: &AccountId, amount: Balance) {
+        let unclaimed_balance = self.get_unclaimed_balance(account_id) + amount;
+        self.unclaimed_balances
+            .insert(account_id, &unclaimed_balance);
+    }
+
+    /// Remove the unclaimed balance of an account for withdrawing
+    pub fn take_unclaimed_balance(&mut self, account_id: &AccountId) -> Option<Balance> {
+        self.unclaimed_balances.remove(account_id)
+    }
+
     pub fn account_exists(&self, account_id: &AccountId) -> bool {
         self.account_map.contains_key(account_id)
     }
@@ -172,42 +350,37 @@ impl AppchainState {
             .account_map
             .get(account_id)
             .expect("This account is not validator");
-        self.validators.get(&validato_id).unwrap().get()
+        self.validators.get(&validato_id)?.get()
     }
 
     fn history_index_set_to_validator_set(&self, vh_set: ValidatorHistoryIndexSet) -> ValidatorSet {
         ValidatorSet {
             seq_num: vh_set.seq_num,
             set_id: vh_set.set_id,
-            validators_len: vh_set.indexes.len() as u32,
+            validators_len: vh_set.validators_len,
         }
     }
 
+    // Get indexes of validators in a validator set, which may be shared with earlier sets
+    fn get_indexes_of_set(&self, vh_set: &ValidatorHistoryIndexSet) -> Vec<ValidatorIndex> {
+        self.validator_index_lists
+            .get(&vh_set.indexes_set_id)
+            .unwrap_or_default()
+    }
+
     fn get_current_validator_histories(
         &self,
         start: ValidatorIndex,
         limit: ValidatorIndex,
     ) -> Option<Vec<LiteValidator>> {
-        let indexes_len = self.validator_indexes.len();
-        let end = std::cmp::min(start + limit, indexes_len as u32);
-        let indexes: Vec<ValidatorIndex> = self.validator_indexes.keys().collect();
-        let mut validators = Vec::new();
-        for index in start..end {
-            let v_index = indexes.get(index as usize).unwrap();
-            let history_list = self
-                .validator_history_lists
-                .get(&v_index)
-                .unwrap()
-                .get()
-                .unwrap()
-                .to_vec();
-            let v_history = history_list
-                .iter()
-                .rev()
-                .find(|h| h.get().unwrap().set_id <= self.validators_nonce);
-            let validator = v_history.unwrap().get().unwrap().to_lite_validator();
-            validators.push(validator);
-        }
+        let indexes = self.get_enabled_validator_indexes();
+        let end = std::cmp::min(start + limit, indexes.len() as u32);
+        let validators = (start..end)
+            .filter_map(|index| {
+                let v_index = indexes.get(index as usize).unwrap();
+                self.get_lite_validator_of_set(v_index, self.validators_nonce)
+            })
+            .collect();
         Some(validators)
     }
 
@@ -217,7 +390,7 @@ impl AppchainState {
         start: ValidatorIndex,
         limit: ValidatorIndex,
     ) -> Option<Vec<LiteValidator>> {
-        let facts_len = self.raw_facts.len().try_into().unwrap_or(0);
+        let facts_len = seq_num_from_len(self.raw_facts.len());
         if facts_len == 0 {
             return Some(Vec::new());
         } else if seq_num == facts_len {
@@ -226,25 +399,15 @@ impl AppchainState {
             let raw_fact = self.raw_facts.get(seq_num as u64).unwrap().get().unwrap();
             match raw_fact {
                 RawFact::ValidatorHistoryIndexSet(vh_set) => {
-                    let mut validators = Vec::new();
-                    let index_set_len = vh_set.indexes.len().try_into().unwrap_or(0);
+                    let indexes = self.get_indexes_of_set(&vh_set);
+                    let index_set_len = seq_num_from_len(indexes.len() as u64);
                     let end = std::cmp::min(start + limit, index_set_len);
-                    for index in start..end {
-                        let v_index = vh_set.indexes.get(index as usize).unwrap();
-                        let history_list = self
-                            .validator_history_lists
-                            .get(&v_index)
-                            .unwrap()
-                            .get()
-                            .unwrap()
-                            .to_vec();
-                        let v_history = history_list
-                            .iter()
-                            .rev()
-                            .find(|h| h.get().unwrap().set_id <= vh_set.set_id);
-                        let validator = v_history.unwrap().get().unwrap().to_lite_validator();
-                        validators.push(validator);
-                    }
+                    let validators = (start..end)
+                        .filter_map(|index| {
+                            let v_index = indexes.get(index as usize).unwrap();
+                            self.get_lite_validator_of_set(v_index, vh_set.set_id)
+                        })
+                        .collect();
                     Some(validators)
                 }
                 _ => None,
@@ -262,17 +425,32 @@ impl AppchainState {
         }
     }
 
+    fn raw_fact_to_view(&self, raw_fact: RawFact) -> RawFactView {
+        match raw_fact {
+            RawFact::ValidatorHistoryIndexSet(vh_set) => RawFactView::ValidatorHistoryIndexSet {
+                seq_num: vh_set.seq_num,
+                set_id: vh_set.set_id,
+                indexes: self.get_indexes_of_set(&vh_set),
+                timestamp: vh_set.timestamp.into(),
+                staked_balance: vh_set.staked_balance.map(|balance| balance.into()),
+            },
+            RawFact::LockAsset(locked) => RawFactView::LockAsset(locked),
+            RawFact::Burn(burned) => RawFactView::Burn(burned),
+        }
+    }
+
     /// Get validator set of the next set_id
     pub fn should_next_validator_set(&self) -> bool {
         let updated_time_from_booting = self.validators_timestamp - self.booting_timestamp;
-        let updated_cycles_from_booting = updated_time_from_booting / VALIDATOR_SET_CYCLE;
+        let updated_cycles_from_booting = updated_time_from_booting / self.validator_set_cycle;
         let now_cycles_from_booting =
-            (env::block_timestamp() - self.booting_timestamp) / VALIDATOR_SET_CYCLE;
+            (env::block_timestamp() - self.booting_timestamp) / self.validator_set_cycle;
 
         let time_for_next = self.validator_set_timestamp != self.validators_timestamp
             && updated_time_from_booting > 0
             && now_cycles_from_booting - updated_cycles_from_booting > 0;
 
+        // A frozen appchain has no new validator set
         return time_for_next && self.status.eq(&AppchainStatus::Booting);
     }
 
@@ -298,17 +476,60 @@ impl AppchainState {
         validators
     }
 
-    // Convert current validators array to struct `ValidatorSet`
+    // Indexes of current validators, except the disabled ones
+    fn get_enabled_validator_indexes(&self) -> Vec<ValidatorIndex> {
+        self.validator_indexes
+            .keys()
+            .filter(|v_index| {
+                let validator_id = self.validator_index_to_id.get(v_index).unwrap();
+                self.get_validator(&validator_id)
+                    .map_or(false, |validator| validator.enabled)
+            })
+            .collect()
+    }
+    /// Whether the validator would be included if the next validator set was created now
+    pub fn is_in_next_validator_set(&self, validator_id: &ValidatorId) -> bool {
+        self.validator_id_to_index
+            .get(validator_id)
+            .map_or(false, |v_index| {
+                self.validator_indexes.get(&v_index).is_some()
+                    && self
+                        .get_validator(validator_id)
+                        .map_or(false, |validator| validator.enabled)
+            })
+    }
     fn get_latest_validator_history_index_set(&self) -> ValidatorHistoryIndexSet {
-        let next_seq_num = self.raw_facts.len().try_into().unwrap();
-        let validator_indexes = self.validator_indexes.keys().collect();
+        let next_seq_num = seq_num_from_len(self.raw_facts.len());
+        let validator_indexes = self.get_enabled_validator_indexes();
         ValidatorHistoryIndexSet {
             seq_num: next_seq_num,
             set_id: self.validators_nonce,
-            indexes: validator_indexes,
+            indexes_set_id: self.validators_nonce,
+            validators_len: validator_indexes.len() as u32,
+            timestamp: env::block_timestamp(),
+            staked_balance: Some(self.staked_balance),
         }
     }
 
+    /// Get the range of set_id of validator sets which are still stored,
+    /// as `(min_available_set_id, current_set_id)`
+    ///
+    /// The validator sets whose facts were pruned are excluded,
+    /// and the range is empty if `min_available_set_id` is greater than `current_set_id`.
+    pub fn get_validator_set_range(&self) -> (SetId, SetId) {
+        let current_set_id = self.validators_nonce - 1;
+        let min_available_set_id = self
+            .raw_facts
+            .iter()
+            .filter_map(|f| f.get())
+            .find_map(|raw_fact| match raw_fact {
+                RawFact::ValidatorHistoryIndexSet(vh_set) => Some(vh_set.set_id),
+                _ => None,
+            })
+            .unwrap_or(current_set_id + 1);
+        (min_available_set_id, current_set_id)
+    }
+
     /// Get validator set of current epoch
     ///
     /// The return data is come from the facts of the appchain
@@ -347,6 +568,302 @@ impl AppchainState {
             _ => false,
         }
     }
+    /// Delegate some OCT tokens to a validator of the appchain
+    ///
+    /// The delegated balance counts in the weight of the validator
+    /// and the staked balance of the appchain.
+    pub fn delegate(
+        &mut self,
+        validator_id: &ValidatorId,
+        delegator_id: &DelegatorId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) {
+        let validator = self
+            .get_validator(validator_id)
+            .expect("Validator not found.");
+        assert!(
+            self.max_delegation_ratio == 0
+                || AppchainValidator::delegation_ratio_of(
+                    validator.amount,
+                    validator.delegated_total + amount
+                ) <= self.max_delegation_ratio,
+            "The delegation ratio of the validator would exceed the maximum {}%.",
+            self.max_delegation_ratio
+        );
+        self.add_delegation(validator_id, delegator_id, account_id, amount);
+    }
+    /// Restore a delegation whose withdrawal was failed, regardless of the maximum delegation
+    /// ratio, returns false if the validator is removed in the meantime
+    ///
+    /// The amount is moved to the unclaimed balance of the account if it can't be restored.
+    pub fn restore_delegation(
+        &mut self,
+        validator_id: &ValidatorId,
+        delegator_id: &DelegatorId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) -> bool {
+        if self.get_validator(validator_id).is_none() {
+            self.add_unclaimed_balance(account_id, amount);
+            return false;
+        }
+        self.add_delegation(validator_id, delegator_id, account_id, amount);
+        true
+    }
+    fn add_delegation(
+        &mut self,
+        validator_id: &ValidatorId,
+        delegator_id: &DelegatorId,
+        account_id: &AccountId,
+        amount: Balance,
+    ) {
+        let mut validator_option = self
+            .validators
+            .get(validator_id)
+            .expect("Validator not found.");
+        let mut validator = validator_option.get().unwrap();
+        if self.status == AppchainStatus::Booting {
+            // Try to create validators_history before delegate.
+            self.create_validators_history(false);
+            self.validators_timestamp = env::block_timestamp();
+        }
+        match validator.delegators.get(delegator_id) {
+            Some(mut delegator_option) => {
+                let mut delegator = delegator_option.get().unwrap();
+                assert_eq!(
+                    &delegator.account_id, account_id,
+                    "The delegator id is used by another account."
+                );
+                delegator.amount += amount;
+                delegator_option.set(&delegator);
+            }
+            None => {
+                validator.delegators.insert(
+                    delegator_id,
+                    &LazyOption::new(
+                        StorageKey::AppchainDelegator(
+                            self.appchain_id.clone(),
+                            validator_id.clone(),
+                            delegator_id.clone(),
+                        )
+                        .into_bytes(),
+                        Some(&AppchainDelegator {
+                            delegator_id: delegator_id.clone(),
+                            account_id: account_id.clone(),
+                            amount,
+                            block_height: env::block_index(),
+                        }),
+                    ),
+                );
+                let delegator_index = validator.delegator_last_index + 1;
+                validator
+                    .delegator_id_to_index
+                    .insert(delegator_id, &delegator_index);
+                validator
+                    .delegator_index_to_id
+                    .insert(&delegator_index, delegator_id);
+                validator.delegator_indexes.insert(&delegator_index, &true);
+                validator.delegator_last_index = delegator_index;
+            }
+        }
+        validator.delegated_total += amount;
+        validator_option.set(&validator);
+        self.staked_balance += amount;
+        self.record_validator_history(validator_id.clone());
+    }
+    /// Withdraw some delegated balance of a delegator from a validator of the appchain,
+    /// the delegator is removed if nothing is left
+    pub fn undelegate(
+        &mut self,
+        validator_id: &ValidatorId,
+        delegator_id: &DelegatorId,
+        amount: Balance,
+    ) {
+        let mut validator_option = self
+            .validators
+            .get(validator_id)
+            .expect("Validator not found.");
+        let mut validator = validator_option.get().unwrap();
+        let mut delegator_option = validator
+            .delegators
+            .get(delegator_id)
+            .expect("Delegator not found.");
+        let mut delegator = delegator_option.get().unwrap();
+        assert!(
+            delegator.amount >= amount,
+            "Insufficient delegated balance."
+        );
+        if self.status == AppchainStatus::Booting {
+            // Try to create validators_history before undelegate.
+            self.create_validators_history(false);
+            self.validators_timestamp = env::block_timestamp();
+        }
+        delegator.amount -= amount;
+        if delegator.amount > 0 {
+            delegator_option.set(&delegator);
+        } else {
+            delegator_option.remove();
+            validator.delegators.remove(delegator_id);
+            if let Some(delegator_index) = validator.delegator_id_to_index.remove(delegator_id) {
+                validator.delegator_index_to_id.remove(&delegator_index);
+                validator.delegator_indexes.remove(&delegator_index);
+            }
+        }
+        validator.delegated_total -= amount;
+        validator_option.set(&validator);
+        self.staked_balance -= amount;
+        self.record_validator_history(validator_id.clone());
+    }
+    /// Panics if the staked amount of a validator can't be reduced by `amount`
+    pub fn assert_stake_reducible(
+        &self,
+        validator_id: &ValidatorId,
+        amount: Balance,
+        minimum_staking_amount: Balance,
+    ) {
+        let validator = self
+            .get_validator(validator_id)
+            .expect("Validator not found.");
+        assert!(amount > 0, "The amount should be greater than 0.");
+        assert!(
+            validator.amount >= amount + minimum_staking_amount,
+            "The remaining staked amount should be at least the minimum staking amount."
+        );
+        assert!(
+            self.max_delegation_ratio == 0
+                || AppchainValidator::delegation_ratio_of(
+                    validator.amount - amount,
+                    validator.delegated_total
+                ) <= self.max_delegation_ratio,
+            "The delegation ratio of the validator would exceed the maximum {}%.",
+            self.max_delegation_ratio
+        );
+    }
+    /// Reduce the staked amount of a validator without removing it
+    pub fn reduce_stake(&mut self, validator_id: &ValidatorId, amount: Balance) {
+        let mut validator_option = self
+            .validators
+            .get(validator_id)
+            .expect("Validator not found.");
+        let mut validator = validator_option.get().unwrap();
+        if self.status == AppchainStatus::Booting {
+            // Try to create validators_history before reduce stake.
+            self.create_validators_history(false);
+            self.validators_timestamp = env::block_timestamp();
+        }
+        validator.amount -= amount;
+        validator_option.set(&validator);
+        self.staked_balance -= amount;
+        self.record_validator_history(validator_id.clone());
+    }
+    /// Restore the staked amount of a validator whose reduction was failed,
+    /// returns false if the validator is removed in the meantime
+    ///
+    /// The amount is moved to the unclaimed balance of the refund account if it can't be restored.
+    pub fn restore_stake(
+        &mut self,
+        validator_id: &ValidatorId,
+        refund_account: &AccountId,
+        amount: Balance,
+    ) -> bool {
+        let mut validator_option = match self.validators.get(validator_id) {
+            Some(validator_option) => validator_option,
+            None => {
+                self.add_unclaimed_balance(refund_account, amount);
+                return false;
+            }
+        };
+        let mut validator = validator_option.get().unwrap();
+        if self.status == AppchainStatus::Booting {
+            // Try to create validators_history before restore stake.
+            self.create_validators_history(false);
+            self.validators_timestamp = env::block_timestamp();
+        }
+        validator.amount += amount;
+        validator_option.set(&validator);
+        self.staked_balance += amount;
+        self.record_validator_history(validator_id.clone());
+        true
+    }
+    /// Set the staked amount of a validator for corrections, returns the previous amount
+    ///
+    /// The staked balance of the appchain is reconciled by the difference.
+    pub fn adjust_validator_stake(
+        &mut self,
+        validator_id: &ValidatorId,
+        new_amount: Balance,
+    ) -> Balance {
+        let mut validator_option = self
+            .validators
+            .get(validator_id)
+            .expect("Validator not found.");
+        let mut validator = validator_option.get().unwrap();
+        let old_amount = validator.amount;
+        assert!(
+            self.staked_balance + new_amount >= old_amount,
+            "The staked balance of the appchain would be negative."
+        );
+        if self.status == AppchainStatus::Booting {
+            // Try to create validators_history before the change.
+            self.create_validators_history(false);
+            self.validators_timestamp = env::block_timestamp();
+        }
+        self.staked_balance = self.staked_balance + new_amount - old_amount;
+        validator.amount = new_amount;
+        validator_option.set(&validator);
+        self.record_validator_history(validator_id.clone());
+        old_amount
+    }
+    /// Set the account to receive refunds of the stake of a validator
+    pub fn set_validator_refund_account(
+        &mut self,
+        validator_id: &ValidatorId,
+        refund_account: Option<AccountId>,
+    ) {
+        let mut validator_option = self
+            .validators
+            .get(validator_id)
+            .expect("Validator not found.");
+        let mut validator = validator_option.get().unwrap();
+        validator.refund_account = refund_account;
+        validator_option.set(&validator);
+    }
+    /// Enable or disable a validator, the stake of a disabled validator is kept,
+    /// but it is excluded from the next validator set
+    pub fn set_validator_enabled(&mut self, validator_id: &ValidatorId, enabled: bool) {
+        let mut validator_option = self
+            .validators
+            .get(validator_id)
+            .expect("Validator not found.");
+        let mut validator = validator_option.get().unwrap();
+        assert_ne!(
+            validator.enabled, enabled,
+            "The validator is already in this state."
+        );
+        // Try to create validators_history before the change.
+        self.create_validators_history(false);
+        validator.enabled = enabled;
+        validator_option.set(&validator);
+        if self.status == AppchainStatus::Booting {
+            self.validators_timestamp = env::block_timestamp();
+        }
+    }
+    /// Register a validator directly with the given account, only for staging appchain
+    pub fn seed_validator(
+        &mut self,
+        validator_id: &ValidatorId,
+        account_id: &AccountId,
+        amount: &Balance,
+    ) {
+        assert_eq!(
+            self.status,
+            AppchainStatus::Staging,
+            "Validators can only be seeded in staging."
+        );
+        self.assert_validator_is_not_registered(validator_id, account_id);
+        self.update_validator_amount(validator_id, account_id, amount);
+    }
     // Internal logic for updating staking amount of a validator
     fn update_validator_amount(
         &mut self,
@@ -411,6 +928,10 @@ impl AppchainState {
                                 )
                                 .into_bytes(),
                             ),
+                            delegated_total: 0,
+                            enabled: true,
+                            refund_account: None,
+                            key_type: self.validator_key_type.clone(),
                         }),
                     ),
                 );
@@ -461,7 +982,7 @@ impl AppchainState {
             .get()
             .unwrap()
             .to_validator_history(set_id);
-        let next_validator_history_index = validator_history_list.len().try_into().unwrap();
+        let next_validator_history_index = seq_num_from_len(validator_history_list.len());
         validator_history_list.push(&LazyOption::new(
             StorageKey::ValidatorHistory {
                 appchain_id: self.appchain_id.clone(),
@@ -487,10 +1008,25 @@ impl AppchainState {
     // Internal logic for creating validators history record
     pub fn create_validators_history(&mut self, for_boot: bool) {
         if self.should_next_validator_set() || for_boot {
-            log!("validator_indexes length {}", self.validator_indexes.len());
-            if self.validator_indexes.len() > 0 {
-                let next_seq_num = self.raw_facts.len().try_into().unwrap();
-                let validator_indexes = self.validator_indexes.keys().collect();
+            let validator_indexes = self.get_enabled_validator_indexes();
+            log!("validator_indexes length {}", validator_indexes.len());
+            if validator_indexes.len() > 0 {
+                // The indexes are only stored if they are changed since the latest validator set
+                let indexes_set_id = match self.latest_indexes_set_id {
+                    Some(set_id)
+                        if self.validator_index_lists.get(&set_id).as_ref()
+                            == Some(&validator_indexes) =>
+                    {
+                        set_id
+                    }
+                    _ => {
+                        self.validator_index_lists
+                            .insert(&self.validators_nonce, &validator_indexes);
+                        self.validators_nonce
+                    }
+                };
+                self.latest_indexes_set_id = Some(indexes_set_id);
+                let next_seq_num = seq_num_from_len(self.raw_facts.len());
                 let raw_fact = LazyOption::new(
                     StorageKey::RawFact {
                         appchain_id: self.appchain_id.clone(),
@@ -501,7 +1037,10 @@ impl AppchainState {
                         ValidatorHistoryIndexSet {
                             seq_num: next_seq_num,
                             set_id: self.validators_nonce,
-                            indexes: validator_indexes,
+                            indexes_set_id,
+                            validators_len: validator_indexes.len() as u32,
+                            timestamp: env::block_timestamp(),
+                            staked_balance: Some(self.staked_balance),
                         },
                     )),
                 );
@@ -511,8 +1050,12 @@ impl AppchainState {
             }
         }
     }
-    /// Remove a validator from the appchain
-    pub fn remove_validator(&mut self, validator_id: &ValidatorId) -> Balance {
+    /// Take a validator out of the appchain before its stake is refunded,
+    /// returns the removed balance
+    ///
+    /// The record of the validator and its account are kept until the refund is resolved,
+    /// by `drop_removed_validator` if it succeeds or `restore_validator` if it fails.
+    pub fn take_validator(&mut self, validator_id: &ValidatorId) -> Balance {
         if let Some(validator) = self.get_validator(validator_id) {
             if self.status.eq(&AppchainStatus::Booting) {
                 // Try to create validators_history before remove.
@@ -521,71 +1064,190 @@ impl AppchainState {
             }
             let removed_balance = validator.get_staked_balance_including_delegators();
             self.staked_balance -= removed_balance;
-            self.removed_validators.insert(
-                &validator_id,
-                &LazyOption::new(
-                    StorageKey::AppchainValidator(self.appchain_id.clone(), validator_id.clone())
-                        .into_bytes(),
-                    Some(&validator),
+            // The index may be missing after a partial failure, which should not block the removal.
+            match self.validator_id_to_index.get(&validator_id) {
+                Some(v_index) => {
+                    self.validator_indexes.remove(&v_index);
+                }
+                None => log!(
+                    "Warning: validator {} has no index, skip removing its index.",
+                    validator_id
                 ),
-            );
-            let v_index = self.validator_id_to_index.get(&validator_id).unwrap();
-            self.validator_indexes.remove(&v_index);
+            }
             self.validators.remove(&validator_id);
-            self.account_map.remove(&validator.account_id);
             removed_balance
         } else {
             0
         }
     }
+    /// Drop the record of a validator taken out of the appchain, after its stake is refunded
+    ///
+    /// The balances of its delegators are moved to their unclaimed balances.
+    pub fn drop_removed_validator(&mut self, validator_id: &ValidatorId) {
+        if self.validators.get(validator_id).is_some() {
+            return;
+        }
+        let mut validator_option: LazyOption<AppchainValidator> = LazyOption::new(
+            StorageKey::AppchainValidator(self.appchain_id.clone(), validator_id.clone())
+                .into_bytes(),
+            None,
+        );
+        if let Some(validator) = validator_option.get() {
+            validator
+                .delegators
+                .values_as_vector()
+                .iter()
+                .filter_map(|delegator_option| delegator_option.get())
+                .for_each(|delegator| {
+                    self.add_unclaimed_balance(&delegator.account_id, delegator.amount)
+                });
+            validator.clear_extra_storage();
+            validator_option.remove();
+            self.account_map.remove(&validator.account_id);
+        }
+    }
+    /// Put back a validator taken out of the appchain whose refund was failed,
+    /// returns the restored balance
+    pub fn restore_validator(&mut self, validator_id: &ValidatorId) -> Balance {
+        if self.validators.get(validator_id).is_some() {
+            return 0;
+        }
+        let validator_option: LazyOption<AppchainValidator> = LazyOption::new(
+            StorageKey::AppchainValidator(self.appchain_id.clone(), validator_id.clone())
+                .into_bytes(),
+            None,
+        );
+        let validator = match validator_option.get() {
+            Some(validator) => validator,
+            None => return 0,
+        };
+        if self.status.eq(&AppchainStatus::Booting) {
+            // Try to create validators_history before restore.
+            self.create_validators_history(false);
+            self.validators_timestamp = env::block_timestamp();
+        }
+        self.validators.insert(validator_id, &validator_option);
+        let restored_balance = validator.get_staked_balance_including_delegators();
+        self.staked_balance += restored_balance;
+        if let Some(v_index) = self.validator_id_to_index.get(validator_id) {
+            self.validator_indexes.insert(&v_index, &true);
+            self.record_validator_history(validator_id.clone());
+        }
+        restored_balance
+    }
+    // Get the validator history index set fact by nonce
+    fn get_validator_history_index_set_by_nonce(
+        &self,
+        validators_nonce: &u32,
+    ) -> Option<ValidatorHistoryIndexSet> {
+        self.raw_facts
+            .iter()
+            .filter_map(|f| f.get())
+            .find_map(|raw_fact| match raw_fact {
+                RawFact::ValidatorHistoryIndexSet(vh_set) if vh_set.set_id.eq(validators_nonce) => {
+                    Some(vh_set)
+                }
+                _ => None,
+            })
+    }
+    // Get the latest history of a validator not after a validator set,
+    // `None` if the history of the validator is missing
+    fn get_lite_validator_of_set(
+        &self,
+        v_index: &ValidatorIndex,
+        set_id: SetId,
+    ) -> Option<LiteValidator> {
+        let history_list = self.validator_history_lists.get(v_index)?.get()?.to_vec();
+        history_list
+            .iter()
+            .rev()
+            .filter_map(|h| h.get())
+            .find(|h| h.set_id <= set_id)
+            .map(|h| h.to_lite_validator())
+    }
+    /// Get total staked balance of the appchain when a validator set was created
+    pub fn get_staked_balance_at_set(&self, set_id: &u32) -> Option<Balance> {
+        self.get_validator_history_index_set_by_nonce(set_id)
+            .and_then(|vh_set| vh_set.staked_balance)
+    }
+
     /// Get a validators history record by nonce
     pub fn get_validator_set_by_nonce(&self, validators_nonce: &u32) -> Option<ValidatorSet> {
-        let validator_history_set_facts = self
-            .raw_facts
-            .iter()
-            .filter(|f| match f.get().unwrap() {
-                RawFact::ValidatorHistoryIndexSet(vh_set) => vh_set.set_id.eq(validators_nonce),
-                _ => false,
+        self.get_validator_history_index_set_by_nonce(validators_nonce)
+            .map(|vh_set| self.history_index_set_to_validator_set(vh_set))
+    }
+    /// Get validators in a validator set sorted by weight descending, ties broken by id
+    pub fn get_ranked_validators_of_set(&self, set_id: &SetId) -> Option<Vec<LiteValidator>> {
+        self.get_validator_history_index_set_by_nonce(set_id)
+            .map(|vh_set| {
+                let mut validators: Vec<LiteValidator> = self
+                    .get_indexes_of_set(&vh_set)
+                    .iter()
+                    .filter_map(|v_index| self.get_lite_validator_of_set(v_index, vh_set.set_id))
+                    .collect();
+                validators.sort_by(|a, b| b.weight.0.cmp(&a.weight.0).then(a.id.cmp(&b.id)));
+                validators
+            })
+    }
+    /// Get SCALE encoded `Vec<([u8; 32], u128)>` of ids and weights of validators
+    /// in a validator set, sorted by validator id
+    pub fn get_validator_set_scale_encoded(&self, validators_nonce: &u32) -> Option<Vec<u8>> {
+        self.get_validator_history_index_set_by_nonce(validators_nonce)
+            .map(|vh_set| {
+                let mut validators: Vec<([u8; 32], u128)> = self
+                    .get_indexes_of_set(&vh_set)
+                    .iter()
+                    .filter_map(|v_index| self.get_lite_validator_of_set(v_index, vh_set.set_id))
+                    .map(|v| {
+                        let mut id = [0u8; 32];
+                        id.copy_from_slice(&hex::decode(&v.id[2..]).expect("Invalid validator id"));
+                        (id, v.weight.0)
+                    })
+                    .collect();
+                validators.sort_by(|a, b| a.0.cmp(&b.0));
+                validators.encode()
             })
-            .collect::<Vec<_>>();
-        if validator_history_set_facts.len() > 0 {
-            match validator_history_set_facts.get(0).unwrap().get().unwrap() {
-                RawFact::ValidatorHistoryIndexSet(vh_set) => {
-                    Option::from(self.history_index_set_to_validator_set(vh_set))
-                }
-                _ => Option::None,
-            }
-        } else {
-            Option::None
-        }
     }
     /// Freeze current appchain
     pub fn freeze(&mut self) {
-        // TODO!
+        self.status = AppchainStatus::Frozen;
+        self.frozen_at = env::block_timestamp();
+    }
+    /// Unfreeze current appchain, it goes back to booting
+    pub fn unfreeze(&mut self) {
+        self.status = AppchainStatus::Booting;
+        self.frozen_at = 0;
     }
     /// Pass auditing of current appchain
     pub fn pass_auditing(&mut self) {
         self.status = AppchainStatus::Voting;
     }
+    /// Emit an event of the appchain with the next event sequence number
+    pub fn emit_event(&mut self, event: RelayEvent) {
+        event.emit(self.event_seq);
+        self.event_seq += 1;
+    }
     /// Go staging of current appchain
     pub fn go_staging(&mut self) {
         self.status = AppchainStatus::Staging;
+        self.staging_started_at = env::block_timestamp();
     }
-    /// Lock some token on current appchain
+    /// Lock some token on current appchain, return the seq_num of the created fact
+    ///
+    /// If asset facts are not persisted, no fact is created and `None` is returned.
     pub fn lock_token(
         &mut self,
         receiver: String,
         sender_id: AccountId,
         token_id: AccountId,
         amount: u128,
-    ) {
+    ) -> Option<SeqNum> {
         let new_amount = self.total_locked_tokens.get(&token_id).unwrap_or(0) + amount;
         self.total_locked_tokens.insert(&token_id, &new_amount);
-        let next_seq_num = self.raw_facts.len().try_into().unwrap();
-        let epoch_number: u32 = ((env::block_timestamp() - self.booting_timestamp)
-            / VALIDATOR_SET_CYCLE)
-            .try_into()
-            .unwrap();
+        if !self.persist_asset_facts {
+            return None;
+        }
+        let next_seq_num = seq_num_from_len(self.raw_facts.len());
         self.raw_facts.push(&LazyOption::new(
             StorageKey::RawFact {
                 appchain_id: self.appchain_id.clone(),
@@ -598,24 +1260,126 @@ impl AppchainState {
                 sender_id,
                 receiver,
                 amount: U128::from(amount),
+                timestamp: env::block_timestamp().into(),
             })),
         ));
+        Some(next_seq_num)
     }
 
     pub fn message_set_used(&mut self, nonce: u64) {
         self.used_messages.insert(&nonce, &true);
     }
 
+    /// Release a message whose execution was failed, so it can be relayed again
+    pub fn message_set_unused(&mut self, nonce: u64) {
+        self.used_messages.remove(&nonce);
+    }
+
     pub fn is_message_used(&self, nonce: u64) -> bool {
-        self.used_messages.get(&nonce).is_some()
+        nonce < self.used_messages_watermark || self.used_messages.get(&nonce).is_some()
+    }
+
+    /// Mark an unlock message as used without executing it, and record the reason
+    pub fn record_failed_unlock(&mut self, nonce: u64, payload: &BurnAssetPayload, reason: String) {
+        self.message_set_used(nonce);
+        self.failed_unlocks.push(&FailedUnlock {
+            nonce,
+            token_id: payload.token_id.clone(),
+            sender: payload.sender.clone(),
+            receiver_id: payload.receiver_id.clone().into(),
+            amount: payload.amount,
+            reason,
+        });
+    }
+
+    pub fn get_failed_unlocks(&self, start: u64, limit: u64) -> Vec<FailedUnlock> {
+        let end = std::cmp::min(start + limit, self.failed_unlocks.len());
+        (start..end)
+            .map(|index| self.failed_unlocks.get(index).unwrap())
+            .collect()
+    }
+
+    /// Mark a lock message as used and record the failed mint for retrying
+    pub fn record_failed_mint(&mut self, nonce: u64, receiver_id: AccountId, amount: U128) {
+        self.message_set_used(nonce);
+        self.failed_mints.insert(
+            &nonce,
+            &FailedMint {
+                nonce,
+                receiver_id,
+                amount,
+            },
+        );
     }
 
+    /// Whether supply of the native token can be reserved for a mint without exceeding the cap
+    pub fn is_native_mint_within_cap(&self, amount: u128) -> bool {
+        self.native_mint_cap == 0
+            || self.native_minted_supply.saturating_add(amount) <= self.native_mint_cap
+    }
+
+    /// Reserve supply of the native token for a mint, panics if the cap would be exceeded
+    pub fn reserve_native_mint(&mut self, amount: u128) {
+        assert!(
+            self.is_native_mint_within_cap(amount),
+            "The minted supply of native token would exceed the cap {}.",
+            self.native_mint_cap
+        );
+        self.native_minted_supply += amount;
+    }
+
+    /// Release supply of the native token for a failed mint or a burn
+    pub fn release_native_mint(&mut self, amount: u128) {
+        self.native_minted_supply = self.native_minted_supply.saturating_sub(amount);
+    }
+
+    /// Record an amount of native token burnt on the appchain, which can be minted on NEAR
+    pub fn record_appchain_burn(&mut self, amount: u128) {
+        self.burned_on_appchain_total += amount;
+    }
+
+    /// Record a mint of native token on NEAR,
+    /// panics if the minted amount would exceed the burnt amount on the appchain
+    pub fn record_native_mint(&mut self, amount: u128) {
+        let new_total = self.minted_on_near_total + amount;
+        assert!(
+            new_total <= self.burned_on_appchain_total,
+            "The minted amount of native token would exceed the burnt amount {} on the appchain.",
+            self.burned_on_appchain_total
+        );
+        self.minted_on_near_total = new_total;
+    }
+
+    /// Revert the record of a failed mint of native token
+    pub fn revert_native_mint(&mut self, amount: u128) {
+        self.minted_on_near_total = self.minted_on_near_total.saturating_sub(amount);
+    }
+
+    /// Remove records of used messages with nonce below `below_nonce`
+    ///
+    /// The watermark is raised to `below_nonce`, so the pruned messages are still treated as used.
+    pub fn prune_used_messages(&mut self, below_nonce: u64) {
+        assert!(
+            below_nonce > self.used_messages_watermark,
+            "The watermark can only be raised."
+        );
+        let pruned_nonces: Vec<u64> = self
+            .used_messages
+            .keys()
+            .filter(|nonce| *nonce < below_nonce)
+            .collect();
+        pruned_nonces.iter().for_each(|nonce| {
+            self.used_messages.remove(nonce);
+        });
+        self.used_messages_watermark = below_nonce;
+    }
+
+    /// Burn native token on current appchain, no fact is created if asset facts are not persisted
     pub fn burn_native_token(&mut self, receiver: String, sender_id: AccountId, amount: u128) {
-        let next_seq_num = self.raw_facts.len().try_into().unwrap();
-        let epoch_number: u32 = ((env::block_timestamp() - self.booting_timestamp)
-            / VALIDATOR_SET_CYCLE)
-            .try_into()
-            .unwrap();
+        if !self.persist_asset_facts {
+            return;
+        }
+        let next_seq_num = seq_num_from_len(self.raw_facts.len());
         self.raw_facts.push(&LazyOption::new(
             StorageKey::RawFact {
                 appchain_id: self.appchain_id.clone(),
@@ -627,6 +1391,7 @@ impl AppchainState {
                 sender_id,
                 receiver,
                 amount: U128::from(amount),
+                timestamp: env::block_timestamp().into(),
             })),
         ));
     }
@@ -635,14 +1400,89 @@ impl AppchainState {
     pub fn unlock_token(&mut self, token_id: AccountId, amount: u128) {
         let new_amount = self.total_locked_tokens.get(&token_id).unwrap_or(0) - amount;
         self.total_locked_tokens.insert(&token_id, &new_amount);
+        let unlocked_amount = self.total_unlocked_tokens.get(&token_id).unwrap_or(0) + amount;
+        self.total_unlocked_tokens
+            .insert(&token_id, &unlocked_amount);
     }
     /// Get total locked amount of a token
     pub fn get_total_locked_amount_of(&self, token_id: &AccountId) -> u128 {
         self.total_locked_tokens.get(token_id).unwrap_or(0)
     }
+    /// Lock facts are only complete if asset facts have always been persisted
+    pub fn assert_asset_facts_persisted(&self) {
+        assert!(
+            self.persist_asset_facts,
+            "Asset facts are not persisted for the appchain."
+        );
+    }
+    /// Compare the tracked total locked amount of a token with the amount
+    /// recomputed by replaying lock facts minus the accumulated unlocked amount
+    pub fn audit_locked_tokens(&self, token_id: &AccountId) -> LockedAudit {
+        self.assert_asset_facts_persisted();
+        let locked_amount = self.get_lock_facts_amount_of(token_id);
+        let unlocked_amount = self.total_unlocked_tokens.get(token_id).unwrap_or(0);
+        LockedAudit {
+            tracked: self.get_total_locked_amount_of(token_id).into(),
+            from_facts: locked_amount.saturating_sub(unlocked_amount).into(),
+        }
+    }
+    /// Get the total amount of lock facts of a token
+    fn get_lock_facts_amount_of(&self, token_id: &AccountId) -> u128 {
+        self.raw_facts
+            .iter()
+            .filter_map(|raw_fact| match raw_fact.get().unwrap() {
+                RawFact::LockAsset(locked) if &locked.token_id == token_id => Some(locked.amount.0),
+                _ => None,
+            })
+            .sum()
+    }
+    /// Seed the accumulated unlocked amount of each locked token as the amount of its
+    /// lock facts minus its tracked total locked amount, for appchains migrated from
+    /// the versions which didn't accumulate unlocked amounts
+    pub fn seed_unlocked_tokens(&mut self) {
+        let token_ids = self.total_locked_tokens.keys_as_vector().to_vec();
+        token_ids.iter().for_each(|token_id| {
+            let unlocked_amount = self
+                .get_lock_facts_amount_of(token_id)
+                .saturating_sub(self.get_total_locked_amount_of(token_id));
+            self.total_unlocked_tokens
+                .insert(token_id, &unlocked_amount);
+        });
+    }
+    /// Get lock facts of a token which are not settled yet
+    ///
+    /// Unlocks of a token settle its lock facts in FIFO order, a lock fact is settled
+    /// once it is fully consumed by the accumulated unlocked amount.
+    pub fn get_unsettled_locks(&self, token_id: &AccountId) -> Vec<Locked> {
+        self.assert_asset_facts_persisted();
+        let mut unlocked_amount = self.total_unlocked_tokens.get(token_id).unwrap_or(0);
+        self.raw_facts
+            .iter()
+            .filter_map(|raw_fact| match raw_fact.get().unwrap() {
+                RawFact::LockAsset(locked) if &locked.token_id == token_id => Some(locked),
+                _ => None,
+            })
+            .filter(|locked| {
+                if unlocked_amount >= locked.amount.0 {
+                    unlocked_amount -= locked.amount.0;
+                    false
+                } else {
+                    unlocked_amount = 0;
+                    true
+                }
+            })
+            .collect()
+    }
+    /// Reset the tracked total locked amount of a token to the recomputed one
+    pub fn repair_locked_tokens(&mut self, token_id: &AccountId) -> LockedAudit {
+        let audit = self.audit_locked_tokens(token_id);
+        self.total_locked_tokens
+            .insert(token_id, &audit.from_facts.0);
+        audit
+    }
     // Get facts by limit number
     pub fn get_facts(&self, start: &SeqNum, limit: &SeqNum) -> Vec<Fact> {
-        let facts_len = self.raw_facts.len().try_into().unwrap_or(0);
+        let facts_len = seq_num_from_len(self.raw_facts.len());
         let end = std::cmp::min(start + limit, facts_len);
         let mut facts = (start.clone()..end)
             .map(|index| {
@@ -659,4 +1499,671 @@ impl AppchainState {
         }
         facts
     }
+    /// Get facts as they are stored, the facts which were pruned are skipped
+    pub fn get_raw_facts(&self, start: SeqNum, limit: SeqNum) -> Vec<RawFactView> {
+        let end = std::cmp::min(start as u64 + limit as u64, self.raw_facts.len());
+        (start as u64..end)
+            .filter_map(|index| {
+                self.raw_facts
+                    .get(index)
+                    .and_then(|raw_fact| raw_fact.get())
+            })
+            .map(|raw_fact| self.raw_fact_to_view(raw_fact))
+            .collect()
+    }
+    /// Get the set_id of the validator set which was in effect at `timestamp`,
+    /// `None` if the appchain was not booting at that time
+    ///
+    /// A validator set takes effect from the start of the cycle in which it is created,
+    /// and a pending next validator set takes effect from the cycle after the last change.
+    pub fn set_id_for_timestamp(&self, timestamp: Timestamp) -> Option<SetId> {
+        if self.booting_timestamp == 0 || timestamp < self.booting_timestamp {
+            return None;
+        }
+        let cycle_of = |ts: Timestamp| (ts - self.booting_timestamp) / self.validator_set_cycle;
+        let cycle = cycle_of(timestamp);
+        if self.should_next_validator_set() && cycle > cycle_of(self.validators_timestamp) {
+            return Some(self.validators_nonce);
+        }
+        for index in (0..self.raw_facts.len()).rev() {
+            if let Some(RawFact::ValidatorHistoryIndexSet(vh_set)) = self
+                .raw_facts
+                .get(index)
+                .and_then(|raw_fact| raw_fact.get())
+            {
+                if cycle_of(vh_set.timestamp) <= cycle {
+                    return Some(vh_set.set_id);
+                }
+            }
+        }
+        None
+    }
+    /// Get validators joined or left since `since_ts`, by comparing the validator set
+    /// which was in effect at `since_ts` with the latest validator set
+    pub fn get_validator_changes_since(&self, since_ts: Timestamp) -> ValidatorChanges {
+        let mut latest_indexes: Option<Vec<ValidatorIndex>> = None;
+        let mut base_indexes: Vec<ValidatorIndex> = Vec::new();
+        for index in (0..self.raw_facts.len()).rev() {
+            if let Some(RawFact::ValidatorHistoryIndexSet(vh_set)) = self
+                .raw_facts
+                .get(index)
+                .and_then(|raw_fact| raw_fact.get())
+            {
+                if vh_set.timestamp <= since_ts {
+                    base_indexes = self.get_indexes_of_set(&vh_set);
+                    break;
+                }
+                if latest_indexes.is_none() {
+                    latest_indexes = Some(self.get_indexes_of_set(&vh_set));
+                }
+            }
+        }
+        let latest_indexes = match latest_indexes {
+            Some(latest_indexes) => latest_indexes,
+            None => return ValidatorChanges::default(),
+        };
+        let to_validator_ids = |indexes: Vec<&ValidatorIndex>| -> Vec<ValidatorId> {
+            indexes
+                .into_iter()
+                .map(|v_index| self.validator_index_to_id.get(v_index).unwrap())
+                .collect()
+        };
+        ValidatorChanges {
+            joined: to_validator_ids(
+                latest_indexes
+                    .iter()
+                    .filter(|v_index| !base_indexes.contains(v_index))
+                    .collect(),
+            ),
+            left: to_validator_ids(
+                base_indexes
+                    .iter()
+                    .filter(|v_index| !latest_indexes.contains(v_index))
+                    .collect(),
+            ),
+        }
+    }
+    /// Get facts created in time range `[from_ts, to_ts)`
+    ///
+    /// Facts are pushed in order of time, so the start of the range is located by binary search.
+    pub fn get_facts_in_time_range(
+        &self,
+        from_ts: Timestamp,
+        to_ts: Timestamp,
+        limit: SeqNum,
+    ) -> Vec<Fact> {
+        let facts_len = self.raw_facts.len();
+        let mut low: u64 = 0;
+        let mut high: u64 = facts_len;
+        while low < high {
+            let mid = (low + high) / 2;
+            if self.raw_facts.get(mid).unwrap().get().unwrap().timestamp() < from_ts {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let mut facts = Vec::new();
+        for index in low..facts_len {
+            if facts.len() as u32 >= limit {
+                break;
+            }
+            let raw_fact = self.raw_facts.get(index).unwrap().get().unwrap();
+            if raw_fact.timestamp() >= to_ts {
+                break;
+            }
+            facts.push(self.raw_fact_to_fact(raw_fact));
+        }
+        facts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{testing_env, MockedBlockchain};
+
+    #[test]
+    fn test_audit_and_repair_locked_tokens() {
+        testing_env!(VMContextBuilder::new().build());
+        let token_id: AccountId = "usdc.testnet".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.lock_token(
+            "receiver".to_string(),
+            "alice".to_string(),
+            token_id.clone(),
+            300,
+        );
+        appchain_state.lock_token(
+            "receiver".to_string(),
+            "bob".to_string(),
+            token_id.clone(),
+            200,
+        );
+        appchain_state.unlock_token(token_id.clone(), 100);
+        assert_eq!(
+            appchain_state.audit_locked_tokens(&token_id),
+            LockedAudit {
+                tracked: U128::from(400),
+                from_facts: U128::from(400),
+            }
+        );
+
+        // Corrupt the tracked value deliberately.
+        appchain_state.total_locked_tokens.insert(&token_id, &1000);
+        let audit = appchain_state.audit_locked_tokens(&token_id);
+        assert_eq!(audit.tracked, U128::from(1000));
+        assert_eq!(audit.from_facts, U128::from(400));
+
+        appchain_state.repair_locked_tokens(&token_id);
+        assert_eq!(appchain_state.get_total_locked_amount_of(&token_id), 400);
+    }
+
+    #[test]
+    fn test_seed_unlocked_tokens() {
+        testing_env!(VMContextBuilder::new().build());
+        let token_id: AccountId = "usdc.testnet".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.lock_token(
+            "receiver".to_string(),
+            "alice".to_string(),
+            token_id.clone(),
+            300,
+        );
+        appchain_state.unlock_token(token_id.clone(), 100);
+        // The unlocked amount wasn't accumulated before the migration.
+        appchain_state.total_unlocked_tokens.remove(&token_id);
+        assert_eq!(
+            appchain_state.audit_locked_tokens(&token_id).from_facts,
+            U128::from(300)
+        );
+
+        appchain_state.seed_unlocked_tokens();
+        assert_eq!(
+            appchain_state.audit_locked_tokens(&token_id),
+            LockedAudit {
+                tracked: U128::from(200),
+                from_facts: U128::from(200),
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_unsettled_locks() {
+        testing_env!(VMContextBuilder::new().build());
+        let token_id: AccountId = "usdc.testnet".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.lock_token(
+            "receiver".to_string(),
+            "alice".to_string(),
+            token_id.clone(),
+            300,
+        );
+        appchain_state.lock_token(
+            "receiver".to_string(),
+            "bob".to_string(),
+            token_id.clone(),
+            200,
+        );
+        assert_eq!(appchain_state.get_unsettled_locks(&token_id).len(), 2);
+
+        appchain_state.unlock_token(token_id.clone(), 300);
+        let unsettled_locks = appchain_state.get_unsettled_locks(&token_id);
+        assert_eq!(unsettled_locks.len(), 1);
+        assert_eq!(unsettled_locks[0].sender_id, "bob".to_string());
+        assert_eq!(unsettled_locks[0].amount, U128::from(200));
+    }
+
+    #[test]
+    fn test_get_unsettled_locks_of_migrated_appchain() {
+        testing_env!(VMContextBuilder::new().build());
+        let token_id: AccountId = "usdc.testnet".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.lock_token(
+            "receiver".to_string(),
+            "alice".to_string(),
+            token_id.clone(),
+            300,
+        );
+        appchain_state.lock_token(
+            "receiver".to_string(),
+            "bob".to_string(),
+            token_id.clone(),
+            200,
+        );
+        appchain_state.unlock_token(token_id.clone(), 300);
+        // The unlocked amount wasn't accumulated before the migration.
+        appchain_state.total_unlocked_tokens.remove(&token_id);
+        assert_eq!(appchain_state.get_unsettled_locks(&token_id).len(), 2);
+
+        appchain_state.seed_unlocked_tokens();
+        let unsettled_locks = appchain_state.get_unsettled_locks(&token_id);
+        assert_eq!(unsettled_locks.len(), 1);
+        assert_eq!(unsettled_locks[0].sender_id, "bob".to_string());
+    }
+
+    #[test]
+    fn test_lock_token_without_persisting_asset_facts() {
+        testing_env!(VMContextBuilder::new().build());
+        let token_id: AccountId = "usdc.testnet".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.persist_asset_facts = false;
+        let seq_num = appchain_state.lock_token(
+            "receiver".to_string(),
+            "alice".to_string(),
+            token_id.clone(),
+            300,
+        );
+        assert_eq!(seq_num, None);
+        assert_eq!(appchain_state.raw_facts.len(), 0);
+        assert_eq!(appchain_state.get_total_locked_amount_of(&token_id), 300);
+
+        appchain_state.burn_native_token("receiver".to_string(), "alice".to_string(), 100);
+        assert_eq!(appchain_state.raw_facts.len(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Asset facts are not persisted for the appchain.")]
+    fn test_audit_locked_tokens_without_persisting_asset_facts() {
+        testing_env!(VMContextBuilder::new().build());
+        let token_id: AccountId = "usdc.testnet".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.persist_asset_facts = false;
+        appchain_state.lock_token(
+            "receiver".to_string(),
+            "alice".to_string(),
+            token_id.clone(),
+            300,
+        );
+        appchain_state.repair_locked_tokens(&token_id);
+    }
+
+    #[test]
+    fn test_get_validator_histories_with_missing_history() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        appchain_state.seed_validator(&"val0".to_string(), &"alice".to_string(), &100);
+        appchain_state.seed_validator(&"val1".to_string(), &"bob".to_string(), &200);
+        appchain_state.create_validators_history(true);
+        assert_eq!(
+            appchain_state
+                .get_validator_histories(0, 0, 10)
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // Drop the history of one validator deliberately.
+        let v_index = appchain_state
+            .validator_id_to_index
+            .get(&"val0".to_string())
+            .unwrap();
+        appchain_state.validator_history_lists.remove(&v_index);
+        let validators = appchain_state.get_validator_histories(0, 0, 10).unwrap();
+        assert_eq!(validators.len(), 1);
+        assert_eq!(validators[0].id, "val1".to_string());
+        assert_eq!(
+            appchain_state
+                .get_validator_histories(1, 0, 10)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_get_validators_window() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        for i in 0..50 {
+            appchain_state.seed_validator(&format!("val{}", i), &format!("account{}", i), &100);
+        }
+        let ids = |start: u32, limit: u32| -> Vec<String> {
+            appchain_state
+                .get_validators(start, limit)
+                .iter()
+                .map(|v| v.validator_id.clone())
+                .collect()
+        };
+
+        assert_eq!(
+            ids(0, 10),
+            (0..10).map(|i| format!("val{}", i)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ids(40, 20),
+            (40..50).map(|i| format!("val{}", i)).collect::<Vec<_>>()
+        );
+        assert_eq!(ids(49, 1), vec!["val49".to_string()]);
+        assert!(ids(50, 10).is_empty());
+        assert!(ids(0, 0).is_empty());
+        assert_eq!(ids(10, u32::MAX).len(), 40);
+    }
+
+    #[test]
+    fn test_get_ranked_validators_of_set() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        appchain_state.seed_validator(&"val0".to_string(), &"alice".to_string(), &100);
+        appchain_state.seed_validator(&"val1".to_string(), &"bob".to_string(), &300);
+        appchain_state.seed_validator(&"val2".to_string(), &"carol".to_string(), &200);
+        appchain_state.create_validators_history(true);
+
+        let set_id = appchain_state.validators_nonce - 1;
+        let validators = appchain_state
+            .get_ranked_validators_of_set(&set_id)
+            .unwrap();
+        let ids: Vec<&str> = validators.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, vec!["val1", "val2", "val0"]);
+        assert!(appchain_state
+            .get_ranked_validators_of_set(&(set_id + 1))
+            .is_none());
+    }
+
+    #[test]
+    fn test_stake_records_history_of_changed_validator_only() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        appchain_state.seed_validator(&"val0".to_string(), &"alice".to_string(), &100);
+        appchain_state.seed_validator(&"val1".to_string(), &"bob".to_string(), &200);
+        appchain_state.seed_validator(&"val2".to_string(), &"carol".to_string(), &300);
+        appchain_state.boot();
+        let history_len = |appchain_state: &AppchainState, validator_id: &str| -> u64 {
+            let v_index = appchain_state
+                .validator_id_to_index
+                .get(&validator_id.to_string())
+                .unwrap();
+            appchain_state
+                .validator_history_lists
+                .get(&v_index)
+                .unwrap()
+                .get()
+                .unwrap()
+                .len()
+        };
+        assert_eq!(history_len(&appchain_state, "val1"), 1);
+
+        appchain_state.stake(&"val1".to_string(), &50);
+        assert_eq!(history_len(&appchain_state, "val0"), 1);
+        assert_eq!(history_len(&appchain_state, "val1"), 2);
+        assert_eq!(history_len(&appchain_state, "val2"), 1);
+    }
+
+    #[test]
+    fn test_validator_set_range() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        appchain_state.seed_validator(&"val0".to_string(), &"alice".to_string(), &100);
+        appchain_state.seed_validator(&"val1".to_string(), &"bob".to_string(), &200);
+        assert_eq!(appchain_state.get_validator_set_range(), (1, 0));
+
+        appchain_state.boot();
+        assert_eq!(appchain_state.get_validator_set_range(), (1, 1));
+        appchain_state.create_validators_history(true);
+        appchain_state.create_validators_history(true);
+        assert_eq!(appchain_state.get_validator_set_range(), (1, 3));
+
+        // Prune the fact of the first validator set.
+        appchain_state.raw_facts.get(0).unwrap().remove();
+        assert_eq!(appchain_state.get_validator_set_range(), (2, 3));
+        assert!(appchain_state.get_validator_set_by_nonce(&1).is_none());
+        assert!(appchain_state.get_validator_set_by_nonce(&2).is_some());
+    }
+
+    #[test]
+    fn test_validator_sets_share_unchanged_indexes() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        appchain_state.seed_validator(&"val0".to_string(), &"alice".to_string(), &100);
+        appchain_state.seed_validator(&"val1".to_string(), &"bob".to_string(), &200);
+        let first_set_id = appchain_state.validators_nonce;
+        appchain_state.create_validators_history(true);
+        appchain_state.create_validators_history(true);
+        appchain_state.seed_validator(&"val2".to_string(), &"carol".to_string(), &300);
+        appchain_state.create_validators_history(true);
+
+        // Only the first and the changed validator sets store their indexes.
+        assert!(appchain_state
+            .validator_index_lists
+            .get(&first_set_id)
+            .is_some());
+        assert!(appchain_state
+            .validator_index_lists
+            .get(&(first_set_id + 1))
+            .is_none());
+        assert!(appchain_state
+            .validator_index_lists
+            .get(&(first_set_id + 2))
+            .is_some());
+        assert_eq!(appchain_state.latest_indexes_set_id, Some(first_set_id + 2));
+
+        let validators_lens: Vec<u32> = (first_set_id..first_set_id + 3)
+            .map(|set_id| {
+                appchain_state
+                    .get_validator_set_by_nonce(&set_id)
+                    .unwrap()
+                    .validators_len
+            })
+            .collect();
+        assert_eq!(validators_lens, vec![2, 2, 3]);
+        assert_eq!(
+            appchain_state
+                .get_ranked_validators_of_set(&(first_set_id + 1))
+                .unwrap()
+                .len(),
+            2
+        );
+        let indexes_lens: Vec<usize> = appchain_state
+            .get_raw_facts(0, 10)
+            .into_iter()
+            .filter_map(|raw_fact| match raw_fact {
+                RawFactView::ValidatorHistoryIndexSet { indexes, .. } => Some(indexes.len()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(indexes_lens, vec![2, 2, 3]);
+    }
+
+    #[test]
+    fn test_remove_validator_without_index() {
+        testing_env!(VMContextBuilder::new().build());
+        let validator_id: ValidatorId = "val0".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        appchain_state.seed_validator(&validator_id, &"alice".to_string(), &100);
+
+        // Lose the index entry deliberately.
+        appchain_state.validator_id_to_index.remove(&validator_id);
+        assert_eq!(appchain_state.take_validator(&validator_id), 100);
+        assert!(appchain_state.get_validator(&validator_id).is_none());
+        assert_eq!(appchain_state.staked_balance, 0);
+    }
+
+    #[test]
+    fn test_remove_validator_only_once() {
+        testing_env!(VMContextBuilder::new().build());
+        let validator_id: ValidatorId = "val0".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        appchain_state.seed_validator(&validator_id, &"alice".to_string(), &100);
+
+        assert_eq!(appchain_state.take_validator(&validator_id), 100);
+        assert_eq!(appchain_state.take_validator(&validator_id), 0);
+        appchain_state.drop_removed_validator(&validator_id);
+        assert_eq!(appchain_state.staked_balance, 0);
+        assert_eq!(appchain_state.removed_validators.len(), 0);
+        assert!(!appchain_state.account_exists(&"alice".to_string()));
+        assert!(env::storage_read(
+            &StorageKey::AppchainValidator("testchain".to_string(), validator_id).into_bytes()
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_restore_validator() {
+        testing_env!(VMContextBuilder::new().build());
+        let validator_id: ValidatorId = "val0".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        appchain_state.seed_validator(&validator_id, &"alice".to_string(), &100);
+
+        assert_eq!(appchain_state.take_validator(&validator_id), 100);
+        assert!(appchain_state.get_validator(&validator_id).is_none());
+        assert!(appchain_state
+            .get_validator_by_account(&"alice".to_string())
+            .is_none());
+        assert_eq!(appchain_state.restore_validator(&validator_id), 100);
+        assert_eq!(appchain_state.restore_validator(&validator_id), 0);
+        assert_eq!(appchain_state.staked_balance, 100);
+        assert_eq!(
+            appchain_state
+                .get_validator_by_account(&"alice".to_string())
+                .unwrap()
+                .amount,
+            100
+        );
+        assert_eq!(appchain_state.get_validators(0, 10).len(), 1);
+    }
+
+    #[test]
+    fn test_remove_validator_with_delegations() {
+        testing_env!(VMContextBuilder::new().build());
+        let validator_id: ValidatorId = "val0".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        appchain_state.seed_validator(&validator_id, &"alice".to_string(), &100);
+        appchain_state.delegate(&validator_id, &"bob".to_string(), &"bob".to_string(), 10);
+        appchain_state.delegate(
+            &validator_id,
+            &"carol".to_string(),
+            &"carol".to_string(),
+            20,
+        );
+
+        assert_eq!(appchain_state.take_validator(&validator_id), 130);
+        // The delegations are kept until the refund of the validator is resolved.
+        assert_eq!(appchain_state.get_unclaimed_balance(&"bob".to_string()), 0);
+        appchain_state.drop_removed_validator(&validator_id);
+        assert_eq!(appchain_state.staked_balance, 0);
+        assert_eq!(appchain_state.get_unclaimed_balance(&"bob".to_string()), 10);
+        assert_eq!(
+            appchain_state.get_unclaimed_balance(&"carol".to_string()),
+            20
+        );
+        assert_eq!(
+            appchain_state.take_unclaimed_balance(&"bob".to_string()),
+            Some(10)
+        );
+        assert_eq!(
+            appchain_state.take_unclaimed_balance(&"bob".to_string()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_restore_delegation() {
+        testing_env!(VMContextBuilder::new().build());
+        let validator_id: ValidatorId = "val0".to_string();
+        let delegator_id: DelegatorId = "bob".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        appchain_state.seed_validator(&validator_id, &"alice".to_string(), &100);
+        appchain_state.delegate(&validator_id, &delegator_id, &delegator_id, 10);
+        appchain_state.undelegate(&validator_id, &delegator_id, 10);
+        assert_eq!(appchain_state.staked_balance, 100);
+
+        assert!(appchain_state.restore_delegation(&validator_id, &delegator_id, &delegator_id, 10));
+        assert_eq!(appchain_state.staked_balance, 110);
+        let validator = appchain_state.get_validator(&validator_id).unwrap();
+        assert_eq!(validator.delegated_total, 10);
+        assert_eq!(
+            validator
+                .delegators
+                .get(&delegator_id)
+                .unwrap()
+                .get()
+                .unwrap()
+                .amount,
+            10
+        );
+        assert!(!appchain_state.restore_delegation(
+            &"val1".to_string(),
+            &delegator_id,
+            &delegator_id,
+            10
+        ));
+        assert_eq!(appchain_state.staked_balance, 110);
+        assert_eq!(appchain_state.get_unclaimed_balance(&delegator_id), 10);
+    }
+
+    #[test]
+    fn test_restore_stake() {
+        testing_env!(VMContextBuilder::new().build());
+        let validator_id: ValidatorId = "val0".to_string();
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.status = AppchainStatus::Staging;
+        appchain_state.seed_validator(&validator_id, &"alice".to_string(), &100);
+        appchain_state.reduce_stake(&validator_id, 40);
+        assert_eq!(appchain_state.staked_balance, 60);
+
+        assert!(appchain_state.restore_stake(&validator_id, &"alice".to_string(), 40));
+        assert_eq!(appchain_state.staked_balance, 100);
+        assert_eq!(
+            appchain_state.get_validator(&validator_id).unwrap().amount,
+            100
+        );
+        assert!(!appchain_state.restore_stake(&"val1".to_string(), &"alice".to_string(), 40));
+        assert_eq!(appchain_state.staked_balance, 100);
+        assert_eq!(
+            appchain_state.get_unclaimed_balance(&"alice".to_string()),
+            40
+        );
+    }
+
+    #[test]
+    fn test_seq_num_from_len() {
+        assert_eq!(seq_num_from_len(0), 0);
+        assert_eq!(seq_num_from_len(u32::MAX as u64), u32::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "The length 4294967296 exceeds the maximum sequence number.")]
+    fn test_seq_num_from_len_overflow() {
+        seq_num_from_len(u32::MAX as u64 + 1);
+    }
+
+    #[test]
+    fn test_native_mint_cap() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.native_mint_cap = 100;
+        appchain_state.reserve_native_mint(60);
+        appchain_state.reserve_native_mint(40);
+        assert_eq!(appchain_state.native_minted_supply, 100);
+
+        // Burnt supply can be minted again.
+        appchain_state.release_native_mint(30);
+        appchain_state.reserve_native_mint(30);
+        assert_eq!(appchain_state.native_minted_supply, 100);
+        assert!(!appchain_state.is_native_mint_within_cap(1));
+        appchain_state.release_native_mint(1);
+        assert!(appchain_state.is_native_mint_within_cap(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "The minted supply of native token would exceed the cap 100.")]
+    fn test_native_mint_over_cap() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut appchain_state = AppchainState::new(&"testchain".to_string());
+        appchain_state.native_mint_cap = 100;
+        appchain_state.reserve_native_mint(100);
+        appchain_state.reserve_native_mint(1);
+    }
 }
@@ -1,10 +1,10 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, Vector};
-use near_sdk::{AccountId, Balance, BlockHeight};
+use near_sdk::{AccountId, Balance, BlockHeight, Timestamp};
 
 use super::delegator::{AppchainDelegator, DelegatorHistory, DelegatorHistoryList};
 use crate::types::{
-    DelegatorId, DelegatorIndex, LiteValidator, SeqNum, SetId, Validator, ValidatorId,
+    DelegatorId, DelegatorIndex, KeyType, LiteValidator, SeqNum, SetId, Validator, ValidatorId,
     ValidatorIndex,
 };
 
@@ -14,8 +14,17 @@ const INVALID_DELEGATORS_DATA_OF_VALIDATOR: &'static str = "Invalid delegators d
 pub struct ValidatorHistoryIndexSet {
     pub seq_num: SeqNum,
     pub set_id: u32,
-    // Use LookupMap instead of Vector to save gas.
-    pub indexes: Vec<ValidatorIndex>,
+    /// Set id of the validator set whose indexes of validators are stored
+    /// in `validator_index_lists` of the appchain, the indexes are only stored
+    /// when they are changed, otherwise they are shared with the previous validator set
+    pub indexes_set_id: SetId,
+    /// Number of validators in the validator set
+    pub validators_len: u32,
+    /// Block timestamp when the validator set was created
+    pub timestamp: Timestamp,
+    /// Total staked balance of the appchain when the validator set was created,
+    /// `None` for the validator sets created before it was recorded
+    pub staked_balance: Option<Balance>,
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -23,8 +32,11 @@ pub struct ValidatorHistory {
     pub id: ValidatorId,
     pub set_id: SetId,
     pub account_id: AccountId,
+    /// Staked balance of the validator itself plus delegated balance
     pub weight: Balance,
     pub block_height: BlockHeight,
+    /// Staked balance of the validator itself
+    pub self_stake: Balance,
 }
 
 impl ValidatorHistory {
@@ -33,6 +45,7 @@ impl ValidatorHistory {
             id: self.id.clone(),
             account_id: self.account_id.clone(),
             weight: self.weight.into(),
+            self_stake: self.self_stake.into(),
             block_height: self.block_height,
             // TODO
             delegators_len: 0,
@@ -64,6 +77,15 @@ pub struct AppchainValidator {
     pub delegator_id_to_index: LookupMap<DelegatorId, DelegatorIndex>,
     /// Current delegators by index
     pub delegator_indexes: UnorderedMap<DelegatorIndex, bool>,
+    /// Total balance delegated to the validator,
+    /// will be updated for each delegate/undelegate action
+    pub delegated_total: Balance,
+    /// Whether the validator can be included in the validator set
+    pub enabled: bool,
+    /// Account to receive refunds of the stake, `None` means `account_id`
+    pub refund_account: Option<AccountId>,
+    /// Type of the public key used as `validator_id`
+    pub key_type: KeyType,
 }
 
 impl AppchainValidator {
@@ -74,6 +96,9 @@ impl AppchainValidator {
             account_id: self.account_id.clone(),
             staked_amount: self.amount.into(),
             block_height: self.block_height,
+            enabled: self.enabled,
+            refund_account: self.refund_account.clone(),
+            key_type: self.key_type.clone(),
             delegators: self
                 .delegators
                 .values_as_vector()
@@ -92,8 +117,9 @@ impl AppchainValidator {
             id: self.validator_id.clone(),
             set_id,
             account_id: self.account_id.clone(),
-            weight: self.amount.into(),
+            weight: self.get_staked_balance_including_delegators(),
             block_height: self.block_height,
+            self_stake: self.amount,
         }
     }
     /// Convert to struct `LiteValidator`
@@ -101,7 +127,8 @@ impl AppchainValidator {
         LiteValidator {
             id: self.validator_id.clone(),
             account_id: self.account_id.clone(),
-            weight: self.amount.into(),
+            weight: self.get_staked_balance_including_delegators().into(),
+            self_stake: self.amount.into(),
             block_height: self.block_height,
             delegators_len: 0,
         }
@@ -113,17 +140,26 @@ impl AppchainValidator {
         }
         Option::None
     }
+    /// Get ratio of delegated balance to the balance staked by the validator itself, in percent
+    pub fn get_delegation_ratio(&self) -> u16 {
+        Self::delegation_ratio_of(self.amount, self.delegated_total)
+    }
+    /// Get ratio of `delegated` to `self_stake` in percent, saturated at `u16::MAX`
+    pub fn delegation_ratio_of(self_stake: Balance, delegated: Balance) -> u16 {
+        if delegated == 0 {
+            return 0;
+        }
+        if self_stake == 0 {
+            return u16::MAX;
+        }
+        std::cmp::min(delegated.saturating_mul(100) / self_stake, u16::MAX.into()) as u16
+    }
     /// Get total staked amount of OCT tokens of the validator,
     /// this function will also count all balances of delegators.
     pub fn get_staked_balance_including_delegators(&self) -> Balance {
         self.amount
-            + self
-                .delegators
-                .values_as_vector()
-                .iter()
-                .filter(|d| d.is_some())
-                .map(|d| d.get().unwrap().amount)
-                .sum::<u128>()
+            .checked_add(self.delegated_total)
+            .expect("The staked balance of the validator overflows.")
     }
     /// Clear extra storage used by the validator
     ///
@@ -134,3 +170,85 @@ impl AppchainValidator {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{testing_env, MockedBlockchain};
+
+    fn new_validator(amount: Balance, delegated_total: Balance) -> AppchainValidator {
+        AppchainValidator {
+            validator_id: "validator".to_string(),
+            account_id: "alice".to_string(),
+            amount,
+            block_height: 0,
+            delegators: UnorderedMap::new(b"d".to_vec()),
+            delegator_history_lists: LookupMap::new(b"dhs".to_vec()),
+            delegator_index_to_id: LookupMap::new(b"di".to_vec()),
+            delegator_last_index: 0,
+            delegator_id_to_index: LookupMap::new(b"id".to_vec()),
+            delegator_indexes: UnorderedMap::new(b"dis".to_vec()),
+            delegated_total,
+            enabled: true,
+            refund_account: None,
+            key_type: KeyType::Raw32,
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The staked balance of the validator overflows.")]
+    fn test_staked_balance_including_delegators_overflow() {
+        testing_env!(VMContextBuilder::new().build());
+        let validator = new_validator(u128::MAX, 1);
+        validator.get_staked_balance_including_delegators();
+    }
+
+    #[test]
+    fn test_staked_balance_including_delegators() {
+        testing_env!(VMContextBuilder::new().build());
+        let mut validator = new_validator(100, 300);
+        for index in 0..1000 {
+            validator.delegators.insert(
+                &format!("delegator{}", index),
+                &LazyOption::new(format!("d{}", index).into_bytes(), None),
+            );
+        }
+        // The delegators are not iterated, only the running total is counted.
+        assert_eq!(validator.get_staked_balance_including_delegators(), 400);
+    }
+
+    #[test]
+    fn test_weight_includes_delegated_balance() {
+        testing_env!(VMContextBuilder::new().build());
+        let validator = new_validator(100, 300);
+
+        let lite_validator = validator.to_lite_validator();
+        assert_eq!(lite_validator.weight.0, 400);
+        assert_eq!(lite_validator.self_stake.0, 100);
+
+        let validator_history = validator.to_validator_history(1);
+        assert_eq!(validator_history.weight, 400);
+        assert_eq!(validator_history.self_stake, 100);
+        let lite_validator = validator_history.to_lite_validator();
+        assert_eq!(lite_validator.weight.0, 400);
+        assert_eq!(lite_validator.self_stake.0, 100);
+
+        assert_eq!(validator.to_validator().staked_amount.0, 100);
+    }
+
+    #[test]
+    fn test_delegation_ratio() {
+        testing_env!(VMContextBuilder::new().build());
+        let validator = new_validator(100, 300);
+        assert_eq!(validator.get_delegation_ratio(), 300);
+
+        // Reducing the self stake to 80 keeps the ratio within a cap of 400%,
+        // while reducing it to 50 breaches the cap.
+        assert!(AppchainValidator::delegation_ratio_of(80, 300) <= 400);
+        assert!(AppchainValidator::delegation_ratio_of(50, 300) > 400);
+
+        assert_eq!(AppchainValidator::delegation_ratio_of(0, 0), 0);
+        assert_eq!(AppchainValidator::delegation_ratio_of(0, 300), u16::MAX);
+    }
+}
@@ -1,10 +1,29 @@
-use near_sdk::log;
+use codec::{Decode, Encode};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::{env, log};
+
+pub type MmrHash = [u8; 32];
+
+/// Proof of a leaf in the MMR of an appchain, SCALE-encoded as `Proof` of `pallet-mmr`
+#[derive(Encode, Decode, Clone, Debug)]
+pub struct MmrLeafProof {
+	/// Index of the proved leaf
+	pub leaf_index: u64,
+	/// Number of leaves of the MMR
+	pub leaf_count: u64,
+	/// Hashes of the peaks at the left side, the siblings on the path from the leaf
+	/// to its peak, then the bagged hash of the peaks at the right side
+	pub items: Vec<MmrHash>,
+}
 
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct AppchainProver;
 
 impl AppchainProver {
+	/// Verify that `encoded_messages` are included in the MMR whose root is `mmr_root`
+	///
+	/// The leaf is `keccak256(header_partial ++ keccak256(encoded_messages))`,
+	/// `leaf_proof` is an encoded `MmrLeafProof` of it.
 	pub fn verify(
 		&self,
 		encoded_messages: Vec<u8>,
@@ -12,7 +31,150 @@ impl AppchainProver {
 		leaf_proof: Vec<u8>,
 		mmr_root: Vec<u8>,
 	) -> bool {
-		log!("in appchain prover");
-		true
+		let proof: MmrLeafProof = match Decode::decode(&mut &leaf_proof[..]) {
+			Ok(proof) => proof,
+			Err(_) => {
+				log!("Invalid leaf proof.");
+				return false;
+			}
+		};
+		let mut leaf = header_partial;
+		leaf.extend(env::keccak256(&encoded_messages));
+		match calculate_root(to_hash(&env::keccak256(&leaf)), proof) {
+			Some(root) => root[..] == mmr_root[..],
+			None => false,
+		}
+	}
+}
+
+fn to_hash(bytes: &[u8]) -> MmrHash {
+	let mut hash = [0u8; 32];
+	hash.copy_from_slice(bytes);
+	hash
+}
+
+fn merge(left: &MmrHash, right: &MmrHash) -> MmrHash {
+	let mut bytes = left.to_vec();
+	bytes.extend_from_slice(right);
+	to_hash(&env::keccak256(&bytes))
+}
+
+/// Calculate the root of the MMR from a leaf and its proof, `None` if the proof is malformed
+fn calculate_root(leaf: MmrHash, proof: MmrLeafProof) -> Option<MmrHash> {
+	if proof.leaf_index >= proof.leaf_count {
+		return None;
+	}
+	let mut items = proof.items.into_iter();
+	let mut peaks: Vec<MmrHash> = Vec::new();
+	let mut leaves_before: u64 = 0;
+	// Each peak of height `h` covers `2^h` leaves, from the highest peak at the left
+	for height in (0..64).rev() {
+		let peak_leaves = 1u64 << height;
+		if proof.leaf_count & peak_leaves == 0 {
+			continue;
+		}
+		if proof.leaf_index >= leaves_before + peak_leaves {
+			peaks.push(items.next()?);
+			leaves_before += peak_leaves;
+			continue;
+		}
+		let offset = proof.leaf_index - leaves_before;
+		let mut hash = leaf;
+		for h in 0..height {
+			let sibling = items.next()?;
+			hash = if (offset >> h) & 1 == 1 {
+				merge(&sibling, &hash)
+			} else {
+				merge(&hash, &sibling)
+			};
+		}
+		peaks.push(hash);
+		if proof.leaf_count & (peak_leaves - 1) != 0 {
+			peaks.push(items.next()?);
+		}
+		break;
+	}
+	if items.next().is_some() {
+		return None;
+	}
+	// Bag the peaks from right to left
+	let mut root = peaks.pop()?;
+	while let Some(peak) = peaks.pop() {
+		root = merge(&root, &peak);
+	}
+	Some(root)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use near_sdk::test_utils::VMContextBuilder;
+	use near_sdk::{testing_env, MockedBlockchain};
+
+	/// Proof of the leaf at index 2 in an MMR of 5 leaves
+	fn leaf_proof() -> MmrLeafProof {
+		MmrLeafProof {
+			leaf_index: 2,
+			leaf_count: 5,
+			items: vec![
+				"fdd1f2a1ec75fe968421a41d2282200de6bec6a21f81080a71b1053d9c0120f3",
+				"c1b5d1ae49e22482d2d21501e7723143bd1f74b536aafc1439762ade2dd39168",
+				"157c9118369926e028fa6cf8dfe68c750c1adbd7b0e4918c2b3a23fe4017c732",
+			]
+			.iter()
+			.map(|item| to_hash(&hex::decode(item).unwrap()))
+			.collect(),
+		}
+	}
+
+	fn mmr_root() -> Vec<u8> {
+		hex::decode("f13674d7fcb90a3a6f71da8e7fa6c5aefd50ddca4714af18ff370ea2751de6d1").unwrap()
+	}
+
+	#[test]
+	fn test_verify() {
+		testing_env!(VMContextBuilder::new().build());
+		assert!(AppchainProver.verify(
+			b"encoded messages".to_vec(),
+			b"header partial".to_vec(),
+			leaf_proof().encode(),
+			mmr_root(),
+		));
+	}
+
+	#[test]
+	fn test_verify_tampered() {
+		testing_env!(VMContextBuilder::new().build());
+		assert!(!AppchainProver.verify(
+			b"forged messages".to_vec(),
+			b"header partial".to_vec(),
+			leaf_proof().encode(),
+			mmr_root(),
+		));
+
+		let mut proof = leaf_proof();
+		proof.items[1][0] ^= 1;
+		assert!(!AppchainProver.verify(
+			b"encoded messages".to_vec(),
+			b"header partial".to_vec(),
+			proof.encode(),
+			mmr_root(),
+		));
+
+		let mut proof = leaf_proof();
+		proof.leaf_index = 3;
+		assert!(!AppchainProver.verify(
+			b"encoded messages".to_vec(),
+			b"header partial".to_vec(),
+			proof.encode(),
+			mmr_root(),
+		));
+
+		assert!(!AppchainProver.verify(
+			b"encoded messages".to_vec(),
+			b"header partial".to_vec(),
+			b"invalid proof".to_vec(),
+			mmr_root(),
+		));
 	}
-}
\ No newline at end of file
+}
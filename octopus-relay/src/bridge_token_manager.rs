@@ -1,11 +1,13 @@
 use crate::relayed_bridge_token::BridgingStatus;
+use crate::types::BridgeUtilization;
 use crate::*;
 
 const UNREGISTERED_TOKEN_ID: &'static str = "Unregistered token id";
+const MAX_BRIDGE_TOKEN_DECIMALS: u32 = 24;
 
 /// Interfaces for manager bridge tokens
 pub trait BridgeTokenManager {
-    /// Register a new bridge token
+    /// Register a new bridge token, its decimals should be at most 24
     fn register_bridge_token(
         &mut self,
         token_id: AccountId,
@@ -14,9 +16,15 @@ pub trait BridgeTokenManager {
         decimals: u32,
     );
     /// Pause bridging a token
-    fn pause_bridge_token(&mut self, token_id: AccountId);
+    ///
+    /// If `resume_after_nanos` is set, the token will be resumed automatically after this period.
+    fn pause_bridge_token(&mut self, token_id: AccountId, resume_after_nanos: Option<U64>);
     /// Resume bridging a token
     fn resume_bridge_token(&mut self, token_id: AccountId);
+    /// Close bridging a token, it can't be resumed
+    fn close_bridge_token(&mut self, token_id: AccountId);
+    /// Unregister a bridge token, which should not be locked in any appchain
+    fn unregister_bridge_token(&mut self, token_id: AccountId);
     /// Set bridging permission of token to an appchain
     fn set_bridge_permitted(
         &mut self,
@@ -27,20 +35,23 @@ pub trait BridgeTokenManager {
     /// Set the price of a token
     ///
     /// This function should be called by an oracle which can offer the price of certain token.
-    fn set_bridge_token_price(&mut self, token_id: AccountId, price: U128);
+    /// The update is rejected within the cooldown period after last update, unless `force` is true.
+    fn set_bridge_token_price(&mut self, token_id: AccountId, price: U128, force: Option<bool>);
     /// Get information of a bridge token
     fn get_bridge_token(&self, token_id: AccountId) -> Option<BridgeToken>;
     /// Get permitted amount of a token
     ///
     /// The result is calculated by the total price of all staked balance of OCT token in an appchain
-    /// and the price of certain token.
+    /// and the price of certain token. Bridging is closed for a frozen appchain.
     fn get_bridge_allowed_amount(&self, appchain_id: AppchainId, token_id: AccountId) -> U128;
 }
 
 #[near_bindgen]
 impl BridgeTokenManager for OctopusRelay {
     /// Pause bridging a token
-    fn pause_bridge_token(&mut self, token_id: AccountId) {
+    ///
+    /// If `resume_after_nanos` is set, the token will be resumed automatically after this period.
+    fn pause_bridge_token(&mut self, token_id: AccountId, resume_after_nanos: Option<U64>) {
         self.assert_owner();
         let mut bridge_token = self
             .get_relayed_bridge_token(&token_id)
@@ -49,7 +60,12 @@ impl BridgeTokenManager for OctopusRelay {
             bridge_token.bridging_status() == BridgingStatus::Activated,
             "The bridge is already paused"
         );
-        bridge_token.pause_bridging();
+        let resume_at = resume_after_nanos.map(|resume_after_nanos| {
+            env::block_timestamp()
+                .checked_add(u64::from(resume_after_nanos))
+                .expect("resume_after_nanos is too large.")
+        });
+        bridge_token.pause_bridging(resume_at);
         self.set_relayed_bridge_token(&bridge_token);
     }
     /// Resume bridging a token
@@ -65,7 +81,47 @@ impl BridgeTokenManager for OctopusRelay {
         bridge_token.activate_bridging();
         self.set_relayed_bridge_token(&bridge_token);
     }
-    /// Register a new bridge token
+    /// Close bridging a token, it can't be resumed
+    fn close_bridge_token(&mut self, token_id: AccountId) {
+        self.assert_owner();
+        let mut bridge_token = self
+            .get_relayed_bridge_token(&token_id)
+            .expect(UNREGISTERED_TOKEN_ID);
+        assert!(
+            bridge_token.bridging_status() != BridgingStatus::Closed,
+            "The bridge is already closed."
+        );
+        bridge_token.close_bridging();
+        self.set_relayed_bridge_token(&bridge_token);
+    }
+    /// Unregister a bridge token, which should not be locked in any appchain
+    fn unregister_bridge_token(&mut self, token_id: AccountId) {
+        self.assert_owner();
+        let mut bridge_token = self
+            .get_relayed_bridge_token(&token_id)
+            .expect(UNREGISTERED_TOKEN_ID);
+        // The state of all appchains is loaded to check the locked amount
+        self.appchain_id_list.iter().for_each(|appchain_id| {
+            let locked = self
+                .appchain_states
+                .get(&appchain_id)
+                .and_then(|appchain_state| appchain_state.get())
+                .map_or(0, |appchain_state| {
+                    appchain_state.get_total_locked_amount_of(&token_id)
+                });
+            assert_eq!(
+                locked, 0,
+                "The token is still locked in appchain '{}'.",
+                appchain_id
+            );
+        });
+        bridge_token.clear_bridging_permissions();
+        self.bridge_tokens
+            .remove(&token_id)
+            .expect(UNREGISTERED_TOKEN_ID)
+            .remove();
+    }
+    /// Register a new bridge token, its decimals should be at most 24
     fn register_bridge_token(
         &mut self,
         token_id: AccountId,
@@ -78,6 +134,11 @@ impl BridgeTokenManager for OctopusRelay {
             self.bridge_tokens.get(&token_id).is_none(),
             "The token_id is already registered"
         );
+        assert!(
+            decimals <= MAX_BRIDGE_TOKEN_DECIMALS,
+            "The decimals of bridge token should be at most {}.",
+            MAX_BRIDGE_TOKEN_DECIMALS
+        );
         self.bridge_tokens.insert(
             &token_id,
             &LazyOption::new(
@@ -106,19 +167,58 @@ impl BridgeTokenManager for OctopusRelay {
         let mut bridge_token = self
             .get_relayed_bridge_token(&token_id)
             .expect(UNREGISTERED_TOKEN_ID);
+        if permitted {
+            if let Some((min_decimals, max_decimals)) =
+                self.get_appchain_state(&appchain_id).allowed_decimals
+            {
+                assert!(
+                    bridge_token.decimals() >= min_decimals
+                        && bridge_token.decimals() <= max_decimals,
+                    "The decimals of token should be in range {}..={}.",
+                    min_decimals,
+                    max_decimals
+                );
+            }
+        }
         bridge_token.set_bridging_permission(&appchain_id, &permitted);
         self.set_relayed_bridge_token(&bridge_token);
     }
     /// Set the price of a token
     ///
     /// This function should be called by an oracle which can offer the price of certain token.
-    fn set_bridge_token_price(&mut self, token_id: AccountId, price: U128) {
+    /// The update is rejected within the cooldown period after last update, unless `force` is true.
+    fn set_bridge_token_price(&mut self, token_id: AccountId, price: U128, force: Option<bool>) {
         self.assert_owner();
         let mut bridge_token = self
             .get_relayed_bridge_token(&token_id)
             .expect(UNREGISTERED_TOKEN_ID);
+        self.assert_price_update_allowed(bridge_token.price_updated_at(), force);
+        // The value of the token locked in every appchain changes with the price,
+        // so the state of all appchains is loaded.
+        let locked_amounts: Vec<(AppchainId, Balance)> = self
+            .appchain_id_list
+            .iter()
+            .filter_map(|appchain_id| {
+                let appchain_state = self.appchain_states.get(&appchain_id)?.get()?;
+                Some((
+                    appchain_id,
+                    appchain_state.get_total_locked_amount_of(&token_id),
+                ))
+            })
+            .filter(|(_, locked)| *locked > 0)
+            .collect();
+        let old_vals: Vec<Balance> = locked_amounts
+            .iter()
+            .map(|(_, locked)| bridge_token.value_of(*locked))
+            .collect();
         bridge_token.set_price(&price);
         self.set_relayed_bridge_token(&bridge_token);
+        locked_amounts
+            .iter()
+            .zip(old_vals)
+            .for_each(|((appchain_id, locked), old_val)| {
+                self.replace_bridge_used_val(appchain_id, old_val, bridge_token.value_of(*locked));
+            });
     }
     /// Get information of a bridge token
     fn get_bridge_token(&self, token_id: AccountId) -> Option<BridgeToken> {
@@ -128,7 +228,7 @@ impl BridgeTokenManager for OctopusRelay {
     /// Get permitted amount of a token
     ///
     /// The result is calculated by the total price of all staked balance of OCT token in an appchain
-    /// and the price of certain token.
+    /// and the price of certain token. The limit ratio of the token is used if it is set.
     fn get_bridge_allowed_amount(&self, appchain_id: AppchainId, token_id: AccountId) -> U128 {
         let appchain_state = self.get_appchain_state(&appchain_id);
         assert_eq!(
@@ -139,32 +239,21 @@ impl BridgeTokenManager for OctopusRelay {
         let bridge_token = self
             .get_relayed_bridge_token(&token_id)
             .expect(UNREGISTERED_TOKEN_ID);
+        if bridge_token.bridging_status() == BridgingStatus::Closed {
+            return 0.into();
+        }
         assert!(
             bridge_token.bridging_status() == BridgingStatus::Activated
                 && bridge_token.is_permitted_of(&appchain_id),
             "The bridge is paused or does not exist"
         );
 
-        let staked_balance = appchain_state.staked_balance;
         let token_price = bridge_token.price().0;
-        let limit_val = staked_balance / OCT_DECIMALS_BASE
-            * self.oct_token_price
-            * (self.bridge_limit_ratio as u128)
-            / 10000;
-        let mut total_used_val: Balance = 0;
-        self.bridge_tokens
-            .values_as_vector()
-            .iter()
-            .map(|f| f.get().unwrap())
-            .for_each(|token| {
-                let appchain_state = self.get_appchain_state(&appchain_id);
-                let bt_price = token.price().0;
-                let bt_locked = appchain_state.get_total_locked_amount_of(&token_id);
-                let bt_decimals = token.decimals();
-                let bt_decimals_base = (10 as u128).pow(bt_decimals);
-                let used_val: Balance = bt_locked * bt_price / bt_decimals_base;
-                total_used_val += used_val;
-            });
+        let limit_ratio = bridge_token
+            .limit_ratio_override()
+            .unwrap_or(self.bridge_limit_ratio);
+        let limit_val = self.get_bridge_limit_val(&appchain_state, limit_ratio);
+        let total_used_val = self.get_bridge_used_val(&appchain_state);
 
         if total_used_val >= limit_val {
             return 0.into();
@@ -173,7 +262,7 @@ impl BridgeTokenManager for OctopusRelay {
         let token_decimals = bridge_token.decimals();
         let token_decimals_base = (10 as u128).pow(token_decimals);
 
-        let allowed_amount = rest_val * token_decimals_base / token_price;
+        let allowed_amount = mul_div_saturating(rest_val, token_decimals_base, token_price);
         allowed_amount.into()
     }
 }
@@ -183,9 +272,124 @@ impl OctopusRelay {
     /// Set the price of OCT token
     ///
     /// This function should be called by an oracle which can offer the price of OCT token.
-    pub fn set_oct_token_price(&mut self, price: U128) {
+    /// The update is rejected within the cooldown period after last update, unless `force` is true.
+    pub fn set_oct_token_price(&mut self, price: U128, force: Option<bool>) {
         self.assert_owner();
+        self.assert_price_update_allowed(self.oct_token_price_updated_at, force);
         self.oct_token_price = price.into();
+        self.oct_token_price_updated_at = env::block_timestamp();
+    }
+    /// Set the range of decimals of bridge tokens can be permitted for an appchain,
+    /// `None` means unlimited
+    pub fn set_allowed_decimals(
+        &mut self,
+        appchain_id: AppchainId,
+        allowed_decimals: Option<(u32, u32)>,
+    ) {
+        self.assert_owner();
+        if let Some((min_decimals, max_decimals)) = allowed_decimals {
+            assert!(min_decimals <= max_decimals, "Invalid range of decimals.");
+        }
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.allowed_decimals = allowed_decimals;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    pub fn get_allowed_decimals(&self, appchain_id: AppchainId) -> Option<(u32, u32)> {
+        self.get_appchain_state(&appchain_id).allowed_decimals
+    }
+    /// Set the minimum interval between two price updates of a token, in nanoseconds
+    pub fn set_price_update_cooldown(&mut self, cooldown: U64) {
+        self.assert_owner();
+        self.price_update_cooldown = cooldown.into();
+    }
+
+    pub fn get_price_update_cooldown(&self) -> U64 {
+        self.price_update_cooldown.into()
+    }
+    /// Set the bridge limit ratio of a token, 100 as 1%, `None` to use the global ratio
+    ///
+    /// The total value of bridge tokens locked in an appchain can't exceed the limit
+    /// under this ratio when locking the token.
+    pub fn set_bridge_token_limit_ratio(&mut self, token_id: AccountId, ratio: Option<u16>) {
+        self.assert_owner();
+        if let Some(ratio) = ratio {
+            assert!(ratio <= 10000, "The limit ratio should be at most 10000.");
+        }
+        let mut bridge_token = self
+            .get_relayed_bridge_token(&token_id)
+            .expect(UNREGISTERED_TOKEN_ID);
+        bridge_token.set_limit_ratio_override(ratio);
+        self.set_relayed_bridge_token(&bridge_token);
+    }
+    /// Get the bridge limit ratio in effect for a token
+    pub fn get_bridge_token_limit_ratio(&self, token_id: AccountId) -> u16 {
+        self.get_relayed_bridge_token(&token_id)
+            .expect(UNREGISTERED_TOKEN_ID)
+            .limit_ratio_override()
+            .unwrap_or(self.bridge_limit_ratio)
+    }
+
+    /// Get registered bridge tokens in range `from_index..from_index + limit`
+    pub fn get_bridge_tokens(&self, from_index: u32, limit: u32) -> Vec<BridgeToken> {
+        let bridge_tokens = self.bridge_tokens.values_as_vector();
+        let end = std::cmp::min(from_index.saturating_add(limit), bridge_tokens.len() as u32);
+        (from_index..end)
+            .filter_map(|index| bridge_tokens.get(index as u64).unwrap().get())
+            .map(|bridge_token| bridge_token.to_bridge_token())
+            .collect()
+    }
+
+    pub fn get_num_bridge_tokens(&self) -> u32 {
+        self.bridge_tokens.len() as u32
+    }
+
+    /// Get the used value of bridge tokens against the bridge limit of an appchain
+    pub fn get_bridge_utilization(&self, appchain_id: AppchainId) -> BridgeUtilization {
+        let appchain_state = self.get_appchain_state(&appchain_id);
+        let limit_val = self.get_bridge_limit_val(&appchain_state, self.bridge_limit_ratio);
+        let used_val = self.get_bridge_used_val(&appchain_state);
+        let utilization_bps = if used_val >= limit_val {
+            if used_val > 0 {
+                10000
+            } else {
+                0
+            }
+        } else {
+            (used_val * 10000 / limit_val) as u16
+        };
+        BridgeUtilization {
+            limit_val: limit_val.into(),
+            used_val: used_val.into(),
+            utilization_bps,
+        }
+    }
+
+    // Total value of bridge tokens can be locked in an appchain, under the given limit ratio
+    fn get_bridge_limit_val(&self, appchain_state: &AppchainState, limit_ratio: u16) -> Balance {
+        (appchain_state.staked_balance / (10 as u128).pow(self.oct_decimals))
+            .saturating_mul(self.oct_token_price)
+            .saturating_mul(limit_ratio as u128)
+            / 10000
+    }
+
+    // Total value of bridge tokens locked in an appchain
+    fn get_bridge_used_val(&self, appchain_state: &AppchainState) -> Balance {
+        self.bridge_used_values
+            .get(&appchain_state.appchain_id)
+            .unwrap_or(0)
+    }
+    // Assert the price update is not in the cooldown period of last update
+    fn assert_price_update_allowed(&self, last_updated_at: Timestamp, force: Option<bool>) {
+        if force.unwrap_or(false) || last_updated_at == 0 {
+            return;
+        }
+        let earliest_allowed_at = last_updated_at + self.price_update_cooldown;
+        assert!(
+            env::block_timestamp() >= earliest_allowed_at,
+            "The price can't be updated until timestamp {}.",
+            earliest_allowed_at
+        );
     }
     // Get relayed bridge token by id
     fn get_relayed_bridge_token(&self, token_id: &AccountId) -> Option<RelayedBridgeToken> {
@@ -202,3 +406,14 @@ impl OctopusRelay {
             .set(bridge_token);
     }
 }
+
+// Calculate `a * b / c` without overflow of the intermediate product where the result fits,
+// saturating at `u128::MAX` otherwise
+pub(crate) fn mul_div_saturating(a: u128, b: u128, c: u128) -> u128 {
+    match a.checked_mul(b) {
+        Some(product) => product / c,
+        None => (a / c)
+            .saturating_mul(b)
+            .saturating_add((a % c).checked_mul(b).map_or(u128::MAX, |r| r / c)),
+    }
+}
@@ -1,14 +1,15 @@
 use crate::bridge_token_manager::BridgeTokenManager;
 use crate::native_token_manager::NativeTokenManager;
 use crate::proof_decoder::ProofDecoder;
-use crate::types::{Message, MessagePayload};
+use crate::types::{BridgeDirections, GasConfig, LockResult, Message, MessagePayload};
 use crate::*;
 
 const STORAGE_DEPOSIT_AMOUNT: Balance = 1250000000000000000000;
 
 /// Trait for bridging tokens between token contracts and appchains
 pub trait TokenBridging {
-    /// Lock token in relay contract for an appchain
+    /// Lock token in relay contract for an appchain,
+    /// return the locked amount and the seq_num of the created fact
     fn lock_token(
         &mut self,
         appchain_id: AppchainId,
@@ -16,7 +17,7 @@ pub trait TokenBridging {
         sender_id: AccountId,
         token_id: AccountId,
         amount: u128,
-    ) -> U128;
+    ) -> LockResult;
     /// Unlock token in relay contract for an appchain
     fn unlock_token(
         &mut self,
@@ -61,6 +62,7 @@ pub trait TokenBridging {
         receiver_id: AccountId,
         amount: U128,
         token_id: AccountId,
+        appchain_id: AppchainId,
     ) -> Promise;
     /// Callback for result of unlock token action
     fn resolve_unlock_token(
@@ -70,13 +72,24 @@ pub trait TokenBridging {
         amount: U128,
         message_nonce: u64,
     );
-    fn resolve_mint_native_token(&mut self, appchain_id: AppchainId, message_nonce: u64);
+    fn resolve_mint_native_token(
+        &mut self,
+        appchain_id: AppchainId,
+        receiver_id: AccountId,
+        amount: U128,
+        message_nonce: u64,
+        refund_to: AccountId,
+        refund: Balance,
+    );
+    /// Mint native token on NEAR for a `Lock` message,
+    /// the deposit not used for storage is refunded to the relayer
     fn mint_native_token(
         &mut self,
         appchain_id: AppchainId,
         receiver_id: AccountId,
         amount: U128,
         message_nonce: u64,
+        relayer: AccountId,
     );
     /// Burn native token on near, then mint on appchain
     fn burn_native_token(&mut self, appchain_id: AppchainId, receiver: AccountId, amount: U128);
@@ -87,6 +100,8 @@ pub trait TokenBridging {
         receiver: String,
         amount: u128,
     );
+    /// Relay messages of the appchain, each nonce can only be used once,
+    /// but the nonces are not required to be sequential
     fn relay(
         &mut self,
         appchain_id: AppchainId,
@@ -95,7 +110,14 @@ pub trait TokenBridging {
         leaf_proof: Vec<u8>,
         mmr_root: Vec<u8>,
     );
-    fn execute(&mut self, messages: Vec<Message>, appchain_id: AppchainId, deposit: Balance);
+    /// Callback for executing the remaining messages of a relay, only called by the contract itself
+    fn execute(
+        &mut self,
+        messages: Vec<Message>,
+        appchain_id: AppchainId,
+        deposit: Balance,
+        relayer: AccountId,
+    );
 }
 
 #[near_bindgen]
@@ -107,7 +129,11 @@ impl TokenBridging for OctopusRelay {
         sender_id: AccountId,
         token_id: AccountId,
         amount: u128,
-    ) -> U128 {
+    ) -> LockResult {
+        assert!(
+            self.get_bridge_directions(appchain_id.clone()).allow_lock,
+            "Locking is disabled for the appchain."
+        );
         let allowed_amount: u128 = self
             .get_bridge_allowed_amount(appchain_id.clone(), token_id.clone())
             .into();
@@ -120,10 +146,28 @@ impl TokenBridging for OctopusRelay {
 
         // Try to create validators_history before lock_token.
         appchain_state.create_validators_history(false);
-        appchain_state.lock_token(receiver, sender_id, token_id, amount);
+        let old_locked = appchain_state.get_total_locked_amount_of(&token_id);
+        let seq_num = appchain_state.lock_token(
+            receiver.clone(),
+            sender_id.clone(),
+            token_id.clone(),
+            amount,
+        );
+        appchain_state.emit_event(RelayEvent::TokenLocked {
+            appchain_id: appchain_id.clone(),
+            token_id: token_id.clone(),
+            sender_id,
+            receiver,
+            amount: amount.into(),
+            seq_num,
+        });
         self.set_appchain_state(&appchain_id, &appchain_state);
+        self.update_bridge_used_val(&appchain_id, &token_id, old_locked, old_locked + amount);
 
-        amount.into()
+        LockResult {
+            seq_num,
+            amount: amount.into(),
+        }
     }
 
     #[payable]
@@ -137,6 +181,7 @@ impl TokenBridging for OctopusRelay {
         message_nonce: u64,
     ) -> Promise {
         assert_self();
+        self.assert_not_paused();
         let deposit: Balance = env::attached_deposit();
         let appchain_state = self.get_appchain_state(&appchain_id);
         let total_locked_amount = appchain_state.get_total_locked_amount_of(&token_id);
@@ -220,7 +265,9 @@ impl TokenBridging for OctopusRelay {
                     None,
                     &token_id,
                     1,
-                    FT_TRANSFER_GAS,
+                    self.get_appchain_gas_config(appchain_id.clone())
+                        .ft_transfer
+                        .0,
                 )
                 .then(Promise::new(env::signer_account_id()).transfer(deposit));
             }
@@ -254,6 +301,7 @@ impl TokenBridging for OctopusRelay {
             receiver_id.clone(),
             amount,
             token_id.clone(),
+            appchain_id,
             &env::current_account_id(),
             NO_DEPOSIT,
             SINGLE_CALL_GAS,
@@ -266,8 +314,10 @@ impl TokenBridging for OctopusRelay {
         receiver_id: AccountId,
         amount: U128,
         token_id: AccountId,
+        appchain_id: AppchainId,
     ) -> Promise {
         assert_self();
+        let ft_transfer_gas = self.get_appchain_gas_config(appchain_id).ft_transfer.0;
         let signer = env::signer_account_id();
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
@@ -279,7 +329,7 @@ impl TokenBridging for OctopusRelay {
                     if refund > 0 {
                         Promise::new(signer).transfer(refund);
                     }
-                    ext_token::ft_transfer(receiver_id, amount, None, &token_id, 1, FT_TRANSFER_GAS)
+                    ext_token::ft_transfer(receiver_id, amount, None, &token_id, 1, ft_transfer_gas)
                 } else {
                     Promise::new(signer).transfer(deposit)
                 }
@@ -300,11 +350,34 @@ impl TokenBridging for OctopusRelay {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Successful(_) => {
                 let mut appchain_state = self.get_appchain_state(&appchain_id);
-                appchain_state.unlock_token(token_id, amount.0);
-                appchain_state.message_set_used(message_nonce);
+                let old_locked = appchain_state.get_total_locked_amount_of(&token_id);
+                appchain_state.unlock_token(token_id.clone(), amount.0);
+                appchain_state.emit_event(RelayEvent::TokenUnlocked {
+                    appchain_id: appchain_id.clone(),
+                    token_id: token_id.clone(),
+                    amount,
+                    message_nonce,
+                });
                 self.set_appchain_state(&appchain_id, &appchain_state);
+                self.update_bridge_used_val(
+                    &appchain_id,
+                    &token_id,
+                    old_locked,
+                    old_locked - amount.0,
+                );
+            }
+            PromiseResult::Failed => {
+                // The token is still locked, the message can be relayed again.
+                let mut appchain_state = self.get_appchain_state(&appchain_id);
+                appchain_state.message_set_unused(message_nonce);
+                self.set_appchain_state(&appchain_id, &appchain_state);
+                log!(
+                    "Failed to unlock token '{}' for message {}, amount is {}.",
+                    token_id,
+                    message_nonce,
+                    amount.0
+                );
             }
-            PromiseResult::Failed => unreachable!(),
         }
     }
 
@@ -315,44 +388,101 @@ impl TokenBridging for OctopusRelay {
         receiver_id: AccountId,
         amount: U128,
         message_nonce: u64,
+        relayer: AccountId,
     ) {
+        assert_self();
+        self.assert_not_paused();
         let deposit: Balance = env::attached_deposit();
         assert!(
-            deposit == STORAGE_DEPOSIT_AMOUNT,
-            "Attached deposit should be 0.00125."
+            self.get_bridge_directions(appchain_id.clone())
+                .allow_mint_native,
+            "Minting native token is disabled for the appchain."
+        );
+        assert!(
+            deposit >= STORAGE_DEPOSIT_AMOUNT,
+            "Attached deposit should be at least 0.00125."
         );
         let native_token_id = self
             .get_native_token(appchain_id.clone())
             .expect("Native token is not registered.");
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        // The cap may be reached by other mints after the message is executed,
+        // the mint is recorded as failed rather than panicking, so it can be retried
+        if !appchain_state.is_native_mint_within_cap(amount.0) {
+            log!(
+                "Failed to mint native token for message {}, the minted supply would exceed the cap {}.",
+                message_nonce,
+                appchain_state.native_mint_cap
+            );
+            appchain_state.record_failed_mint(message_nonce, receiver_id, amount);
+            self.set_appchain_state(&appchain_id, &appchain_state);
+            Promise::new(relayer).transfer(deposit);
+            return;
+        }
+        appchain_state.record_native_mint(amount.0);
+        appchain_state.reserve_native_mint(amount.0);
+        self.set_appchain_state(&appchain_id, &appchain_state);
         ext_token::mint(
-            receiver_id,
+            receiver_id.clone(),
             amount,
             &native_token_id,
-            deposit,
-            GAS_FOR_FT_TRANSFER_CALL,
+            STORAGE_DEPOSIT_AMOUNT,
+            self.get_appchain_gas_config(appchain_id.clone()).mint.0,
         )
         .then(ext_self::resolve_mint_native_token(
             appchain_id,
+            receiver_id,
+            amount,
             message_nonce,
+            relayer,
+            deposit - STORAGE_DEPOSIT_AMOUNT,
             &env::current_account_id(),
             0,
             GAS_FOR_FT_TRANSFER_CALL,
         ));
     }
 
-    fn resolve_mint_native_token(&mut self, appchain_id: AppchainId, message_nonce: u64) {
+    fn resolve_mint_native_token(
+        &mut self,
+        appchain_id: AppchainId,
+        receiver_id: AccountId,
+        amount: U128,
+        message_nonce: u64,
+        refund_to: AccountId,
+        refund: Balance,
+    ) {
         assert_self();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Successful(_) => {
-                let mut appchain_state = self.get_appchain_state(&appchain_id);
-                appchain_state.message_set_used(message_nonce);
-                self.set_appchain_state(&appchain_id, &appchain_state);
+                appchain_state.failed_mints.remove(&message_nonce);
+                appchain_state.emit_event(RelayEvent::NativeTokenMinted {
+                    appchain_id: appchain_id.clone(),
+                    receiver_id,
+                    amount,
+                    message_nonce,
+                });
+            }
+            PromiseResult::Failed => {
+                log!(
+                    "Failed to mint native token for message {}, receiver is '{}', amount is {}.",
+                    message_nonce,
+                    receiver_id,
+                    amount.0
+                );
+                appchain_state.release_native_mint(amount.0);
+                appchain_state.revert_native_mint(amount.0);
+                appchain_state.record_failed_mint(message_nonce, receiver_id, amount);
             }
-            PromiseResult::Failed => unreachable!(),
+        }
+        self.set_appchain_state(&appchain_id, &appchain_state);
+        if refund > 0 {
+            Promise::new(refund_to).transfer(refund);
         }
     }
 
+    #[payable]
     fn relay(
         &mut self,
         appchain_id: AppchainId,
@@ -361,17 +491,53 @@ impl TokenBridging for OctopusRelay {
         leaf_proof: Vec<u8>,
         mmr_root: Vec<u8>,
     ) {
+        self.assert_not_paused();
         let deposit: Balance = env::attached_deposit();
         let appchain_state = self.get_appchain_state(&appchain_id);
+        assert_eq!(
+            appchain_state.status,
+            AppchainStatus::Booting,
+            "Messages can only be relayed to a booting appchain."
+        );
+        let trusted_root = appchain_state
+            .mmr_root
+            .clone()
+            .expect("The mmr root of the appchain is not set.");
+        assert_eq!(
+            mmr_root, trusted_root,
+            "The mmr root doesn't match the trusted root of the appchain."
+        );
         let verified: bool = appchain_state.prover.verify(
             encoded_messages.clone(),
             header_partial.clone(),
             leaf_proof.clone(),
-            mmr_root.clone(),
+            trusted_root,
         );
         assert!(verified, "verification failed");
         let messages = self.decode(encoded_messages, header_partial, leaf_proof, mmr_root);
-        self.execute(messages, appchain_id, deposit);
+        assert!(
+            messages.len() <= self.max_messages_per_relay as usize,
+            "Too many messages, the maximum is {} in one relay, please split the batch.",
+            self.max_messages_per_relay
+        );
+        for (index, message) in messages.iter().enumerate() {
+            assert!(
+                !appchain_state.is_message_used(message.nonce),
+                "Message {} is used.",
+                message.nonce
+            );
+            assert!(
+                messages[..index].iter().all(|m| m.nonce != message.nonce),
+                "Message {} is duplicated in the batch.",
+                message.nonce
+            );
+        }
+        self.internal_execute(
+            messages,
+            appchain_id,
+            deposit,
+            env::predecessor_account_id(),
+        );
     }
 
     fn execute(
@@ -379,58 +545,31 @@ impl TokenBridging for OctopusRelay {
         messages: Vec<Message>,
         appchain_id: AppchainId,
         remaining_deposit: Balance,
+        relayer: AccountId,
     ) {
-        if messages.len() > 0 {
-            let appchain_state = self.get_appchain_state(&appchain_id);
-            let message = messages.get(0).unwrap();
-            assert!(
-                !appchain_state.is_message_used(message.nonce),
-                "Message is used"
-            );
-
-            let execution_promise;
-            let next_messages = (&messages[1..messages.len()]).to_vec();
-            let next_remaining_deposit = remaining_deposit - STORAGE_DEPOSIT_AMOUNT;
-            match &message.payload {
-                MessagePayload::BurnAsset(p) => {
-                    execution_promise = ext_self::unlock_token(
-                        appchain_id.clone(),
-                        p.token_id.clone(),
-                        p.sender.clone(),
-                        p.receiver_id.clone(),
-                        p.amount,
-                        message.nonce,
-                        &env::current_account_id(),
-                        STORAGE_DEPOSIT_AMOUNT,
-                        COMPLEX_CALL_GAS,
-                    );
-                }
-                MessagePayload::Lock(p) => {
-                    execution_promise = ext_self::mint_native_token(
-                        appchain_id.clone(),
-                        p.receiver_id.clone().into(),
-                        p.amount,
-                        message.nonce,
-                        &env::current_account_id(),
-                        STORAGE_DEPOSIT_AMOUNT,
-                        2 * SINGLE_CALL_GAS,
-                    );
-                }
-            }
-            execution_promise.then(ext_self::execute(
-                next_messages,
-                appchain_id.clone(),
-                next_remaining_deposit,
-                &env::current_account_id(),
-                NO_DEPOSIT,
-                COMPLEX_CALL_GAS + SIMPLE_CALL_GAS,
-            ));
-        }
+        assert_self();
+        self.internal_execute(messages, appchain_id, remaining_deposit, relayer);
     }
 
     #[payable]
     fn burn_native_token(&mut self, appchain_id: AppchainId, receiver: String, amount: U128) {
         assert_one_yocto();
+        self.assert_not_paused();
+        assert_ne!(
+            self.get_appchain_state(&appchain_id).status,
+            AppchainStatus::Frozen,
+            "The appchain is frozen."
+        );
+        assert!(
+            self.get_bridge_directions(appchain_id.clone())
+                .allow_burn_native,
+            "Burning native token is disabled for the appchain."
+        );
+        assert!(
+            receiver.len() <= self.max_receiver_len as usize,
+            "The receiver should be at most {} bytes.",
+            self.max_receiver_len
+        );
         let native_token_id = self
             .get_native_token(appchain_id.clone())
             .expect("Native token is not registered.");
@@ -441,7 +580,7 @@ impl TokenBridging for OctopusRelay {
             amount,
             &native_token_id,
             1,
-            GAS_FOR_FT_TRANSFER_CALL,
+            self.get_appchain_gas_config(appchain_id.clone()).burn.0,
         )
         .then(ext_self::resolve_burn_native_token(
             appchain_id,
@@ -469,10 +608,256 @@ impl TokenBridging for OctopusRelay {
 
                 // Try to create validators_history before burn_native_token.
                 appchain_state.create_validators_history(false);
-                appchain_state.burn_native_token(receiver, sender_id, amount);
+                appchain_state.burn_native_token(receiver.clone(), sender_id.clone(), amount);
+                appchain_state.release_native_mint(amount);
+                appchain_state.emit_event(RelayEvent::NativeTokenBurned {
+                    appchain_id: appchain_id.clone(),
+                    sender_id,
+                    receiver,
+                    amount: amount.into(),
+                });
                 self.set_appchain_state(&appchain_id, &appchain_state);
             }
-            PromiseResult::Failed => unreachable!(),
+            PromiseResult::Failed => {
+                // Nothing is burnt, so no fact is created.
+                log!(
+                    "Failed to burn native token of '{}', amount is {}.",
+                    sender_id,
+                    amount
+                );
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl OctopusRelay {
+    /// Retry a failed mint of native token, the record is recorded again if the mint fails
+    pub fn retry_mint(&mut self, appchain_id: AppchainId, message_nonce: u64) {
+        self.assert_owner();
+        self.assert_not_paused();
+        assert!(
+            self.get_bridge_directions(appchain_id.clone())
+                .allow_mint_native,
+            "Minting native token is disabled for the appchain."
+        );
+        assert!(
+            self.get_native_token(appchain_id.clone()).is_some(),
+            "Native token is not registered."
+        );
+        // The record is removed before the mint, so it can't be retried twice concurrently
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        let failed_mint = appchain_state
+            .failed_mints
+            .remove(&message_nonce)
+            .expect("Failed mint not found.");
+        self.set_appchain_state(&appchain_id, &appchain_state);
+        ext_self::mint_native_token(
+            appchain_id,
+            failed_mint.receiver_id,
+            failed_mint.amount,
+            message_nonce,
+            env::predecessor_account_id(),
+            &env::current_account_id(),
+            STORAGE_DEPOSIT_AMOUNT,
+            2 * SINGLE_CALL_GAS,
+        );
+    }
+
+    /// Set gas for calls to token contracts in bridging of an appchain, `None` to use the default
+    pub fn set_appchain_gas_override(
+        &mut self,
+        appchain_id: AppchainId,
+        config: Option<GasConfig>,
+    ) {
+        self.assert_owner();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.gas_override = config;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    /// Get gas for calls to token contracts in bridging of an appchain
+    pub fn get_appchain_gas_config(&self, appchain_id: AppchainId) -> GasConfig {
+        self.get_appchain_state(&appchain_id)
+            .gas_override
+            .unwrap_or_default()
+    }
+
+    /// Set bridging directions enabled for an appchain
+    pub fn set_bridge_directions(&mut self, appchain_id: AppchainId, directions: BridgeDirections) {
+        self.assert_owner();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.bridge_directions = directions;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    /// Get bridging directions enabled for an appchain
+    pub fn get_bridge_directions(&self, appchain_id: AppchainId) -> BridgeDirections {
+        self.get_appchain_state(&appchain_id).bridge_directions
+    }
+
+    /// Update the trusted MMR root of an appchain, submitted by the light client updater
+    pub fn update_mmr_root(&mut self, appchain_id: AppchainId, mmr_root: Vec<u8>) {
+        self.assert_owner();
+        assert_eq!(mmr_root.len(), 32, "The mmr root should be 32 bytes.");
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.mmr_root = Some(mmr_root);
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    pub fn get_mmr_root(&self, appchain_id: AppchainId) -> Option<Vec<u8>> {
+        self.get_appchain_state(&appchain_id).mmr_root
+    }
+
+    pub fn get_failed_mints(&self, appchain_id: AppchainId) -> Vec<FailedMint> {
+        self.get_appchain_state(&appchain_id)
+            .failed_mints
+            .values()
+            .collect()
+    }
+
+    /// Execute the verified messages one by one, each in a promise chained to the previous one
+    fn internal_execute(
+        &mut self,
+        messages: Vec<Message>,
+        appchain_id: AppchainId,
+        remaining_deposit: Balance,
+        relayer: AccountId,
+    ) {
+        if messages.len() > 0 {
+            let mut appchain_state = self.get_appchain_state(&appchain_id);
+            let message = messages.get(0).unwrap();
+            let next_messages = (&messages[1..messages.len()]).to_vec();
+            // Messages are checked in `relay`, this only guards against a replay in the meantime
+            if appchain_state.is_message_used(message.nonce) {
+                log!("Message {} is skipped, it is already used.", message.nonce);
+                return self.internal_execute(
+                    next_messages,
+                    appchain_id,
+                    remaining_deposit,
+                    relayer,
+                );
+            }
+
+            let execution_promise;
+            // Extra gas for calls to token contracts, if the appchain has a larger gas override
+            let gas_config = self.get_appchain_gas_config(appchain_id.clone());
+            let default_gas_config = GasConfig::default();
+            let next_remaining_deposit = remaining_deposit.saturating_sub(STORAGE_DEPOSIT_AMOUNT);
+            match &message.payload {
+                MessagePayload::BurnAsset(p) => {
+                    // Skip the message with invalid receiver, rather than aborting the batch
+                    let receiver_id: &AccountId = p.receiver_id.as_ref();
+                    if receiver_id.is_empty()
+                        || receiver_id.eq(&env::current_account_id())
+                        || receiver_id.eq(&p.token_id)
+                    {
+                        log!(
+                            "Message {} is skipped, receiver '{}' is invalid.",
+                            message.nonce,
+                            receiver_id
+                        );
+                        appchain_state.record_failed_unlock(
+                            message.nonce,
+                            p,
+                            "Invalid receiver".to_string(),
+                        );
+                        self.set_appchain_state(&appchain_id, &appchain_state);
+                        return self.internal_execute(
+                            next_messages,
+                            appchain_id,
+                            remaining_deposit,
+                            relayer,
+                        );
+                    }
+                    execution_promise = ext_self::unlock_token(
+                        appchain_id.clone(),
+                        p.token_id.clone(),
+                        p.sender.clone(),
+                        p.receiver_id.clone(),
+                        p.amount,
+                        message.nonce,
+                        &env::current_account_id(),
+                        STORAGE_DEPOSIT_AMOUNT,
+                        COMPLEX_CALL_GAS
+                            + gas_config
+                                .ft_transfer
+                                .0
+                                .saturating_sub(default_gas_config.ft_transfer.0),
+                    );
+                }
+                MessagePayload::Lock(p) => {
+                    // The amount is burnt on the appchain, so it can be minted on NEAR
+                    appchain_state.record_appchain_burn(p.amount.0);
+                    // Skip the message if minting is disabled, it can be retried by `retry_mint`
+                    if !appchain_state.bridge_directions.allow_mint_native {
+                        log!(
+                            "Message {} is skipped, minting native token is disabled.",
+                            message.nonce
+                        );
+                        appchain_state.record_failed_mint(
+                            message.nonce,
+                            p.receiver_id.clone().into(),
+                            p.amount,
+                        );
+                        self.set_appchain_state(&appchain_id, &appchain_state);
+                        return self.internal_execute(
+                            next_messages,
+                            appchain_id,
+                            remaining_deposit,
+                            relayer,
+                        );
+                    }
+                    // Skip the message if the cap would be exceeded, it can be retried by
+                    // `retry_mint` after the cap is raised or some supply is burnt
+                    if !appchain_state.is_native_mint_within_cap(p.amount.0) {
+                        log!(
+                            "Message {} is skipped, the minted supply of native token would exceed the cap {}.",
+                            message.nonce,
+                            appchain_state.native_mint_cap
+                        );
+                        appchain_state.record_failed_mint(
+                            message.nonce,
+                            p.receiver_id.clone().into(),
+                            p.amount,
+                        );
+                        self.set_appchain_state(&appchain_id, &appchain_state);
+                        return self.internal_execute(
+                            next_messages,
+                            appchain_id,
+                            remaining_deposit,
+                            relayer,
+                        );
+                    }
+                    execution_promise = ext_self::mint_native_token(
+                        appchain_id.clone(),
+                        p.receiver_id.clone().into(),
+                        p.amount,
+                        message.nonce,
+                        relayer.clone(),
+                        &env::current_account_id(),
+                        STORAGE_DEPOSIT_AMOUNT,
+                        2 * SINGLE_CALL_GAS
+                            + gas_config.mint.0.saturating_sub(default_gas_config.mint.0),
+                    );
+                }
+            }
+            // The message is used once its execution is scheduled, so it can't be replayed
+            // before the execution is resolved
+            appchain_state.message_set_used(message.nonce);
+            self.set_appchain_state(&appchain_id, &appchain_state);
+            execution_promise.then(ext_self::execute(
+                next_messages,
+                appchain_id.clone(),
+                next_remaining_deposit,
+                relayer,
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                COMPLEX_CALL_GAS + SIMPLE_CALL_GAS,
+            ));
+        } else if remaining_deposit > 0 {
+            // Refund the deposit which is not attached to any message to the relayer
+            Promise::new(relayer).transfer(remaining_deposit);
         }
     }
 }
@@ -0,0 +1,81 @@
+use crate::*;
+
+const EVENT_STANDARD: &'static str = "octopus-relay";
+const EVENT_STANDARD_VERSION: &'static str = "1.0.0";
+
+/// Events of the relay, logged in the format of NEP-297 for indexers
+///
+/// Each event is emitted through its appchain, with an `event_seq` in the data which
+/// increases by 1 for each event of the appchain, so that indexers can dedupe and detect gaps.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum RelayEvent {
+    AppchainRegistered {
+        appchain_id: AppchainId,
+        founder_id: AccountId,
+        bond_tokens: U128,
+    },
+    ValidatorStaked {
+        appchain_id: AppchainId,
+        validator_id: ValidatorId,
+        account_id: AccountId,
+        amount: U128,
+    },
+    ValidatorRemoved {
+        appchain_id: AppchainId,
+        validator_id: ValidatorId,
+        refund_account: AccountId,
+        amount: U128,
+    },
+    TokenLocked {
+        appchain_id: AppchainId,
+        token_id: AccountId,
+        sender_id: AccountId,
+        receiver: String,
+        amount: U128,
+        seq_num: Option<SeqNum>,
+    },
+    TokenUnlocked {
+        appchain_id: AppchainId,
+        token_id: AccountId,
+        amount: U128,
+        message_nonce: u64,
+    },
+    NativeTokenMinted {
+        appchain_id: AppchainId,
+        receiver_id: AccountId,
+        amount: U128,
+        message_nonce: u64,
+    },
+    NativeTokenBurned {
+        appchain_id: AppchainId,
+        sender_id: AccountId,
+        receiver: String,
+        amount: U128,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a RelayEvent,
+}
+
+impl RelayEvent {
+    /// Log the event as `EVENT_JSON:{"standard":..,"version":..,"event":..,"data":..}`,
+    /// with `event_seq` added to the data
+    pub fn emit(&self, event_seq: u64) {
+        let mut event_log = near_sdk::serde_json::to_value(&EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_STANDARD_VERSION,
+            event: self,
+        })
+        .unwrap();
+        event_log["data"]["event_seq"] = event_seq.into();
+        log!("EVENT_JSON:{}", event_log);
+    }
+}
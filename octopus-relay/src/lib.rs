@@ -2,33 +2,38 @@ mod appchain;
 mod appchain_prover;
 mod bridge_token_manager;
 mod bridging;
+mod events;
 mod native_token_manager;
 mod pipeline;
 mod proof_decoder;
 mod relayed_bridge_token;
 mod storage_key;
-// pub mod storage_migration;
+pub mod storage_migration;
 pub mod types;
 use crate::types::Message;
 
-use std::convert::{From, TryInto};
+use std::convert::{From, TryFrom};
 
 use crate::bridging::TokenBridging;
+use crate::events::RelayEvent;
 use crate::storage_key::StorageKey;
 // To conserve gas, efficient serialization is achieved through Borsh (http://borsh.io/)
 use crate::types::{
-    Appchain, AppchainId, AppchainStatus, BridgeToken, Delegator, DelegatorId, Fact, LiteValidator,
-    SeqNum, StorageBalance, Validator, ValidatorId, ValidatorIndex, ValidatorSet,
+    AccountVote, Appchain, AppchainHealth, AppchainId, AppchainStatus, BootingInfo, BridgeToken,
+    ChainSpecInfo, Delegator, DelegatorId, Fact, FactsRequest, FailedMint, FailedRemoval,
+    FailedUnlock, KeyType, LiteValidator, Locked, LockedAudit, RawFactView, RelayConfig,
+    SeedValidator, SeqNum, SetId, StakingDelta, StorageBalance, Validator, ValidatorChanges,
+    ValidatorId, ValidatorIndex, ValidatorSet, ValidatorSetPair, Vote,
 };
 use appchain::metadata::AppchainMetadata;
-use appchain::state::AppchainState;
+use appchain::state::{seq_num_from_len, AppchainState};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, UnorderedMap, Vector};
-use near_sdk::json_types::{ValidAccountId, U128};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, Vector};
+use near_sdk::json_types::{Base64VecU8, ValidAccountId, I128, U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
     assert_one_yocto, assert_self, env, ext_contract, log, near_bindgen, wee_alloc, AccountId,
-    Balance, BlockHeight, Promise, PromiseOrValue, PromiseResult,
+    Balance, BlockHeight, Promise, PromiseOrValue, PromiseResult, Timestamp,
 };
 use relayed_bridge_token::RelayedBridgeToken;
 
@@ -42,13 +47,20 @@ const GAS_FOR_FT_TRANSFER_CALL: u64 = 35 * T_GAS;
 const SINGLE_CALL_GAS: u64 = 50 * T_GAS;
 const COMPLEX_CALL_GAS: u64 = 120 * T_GAS;
 const SIMPLE_CALL_GAS: u64 = 5 * T_GAS;
-const OCT_DECIMALS_BASE: Balance = 1000_000_000_000_000_000;
+const DEFAULT_OCT_DECIMALS: u32 = 18;
+const DEFAULT_MAX_MESSAGES_PER_RELAY: u32 = 20;
+const DEFAULT_MAX_RECEIVER_LEN: u32 = 128;
+// The minimum delegation amount is a tenth of the minimum staking amount by default
+const DEFAULT_MINIMUM_DELEGATION_DIVISOR: u128 = 10;
+const MAX_APPCHAINS_SCANNED_PER_VIEW: u32 = 50;
+const MAX_APPCHAIN_NOTE_LENGTH: usize = 256;
+const MAX_FACTS_PER_MULTI_VIEW: u32 = 100;
 
 const APPCHAIN_METADATA_NOT_FOUND: &'static str = "Appchain metadata not found";
 const APPCHAIN_STATE_NOT_FOUND: &'static str = "Appchain state not found";
 
 // 20 minutes
-const VALIDATOR_SET_CYCLE: u64 = 20 * 60000000000;
+const DEFAULT_VALIDATOR_SET_CYCLE: u64 = 20 * 60000000000;
 // const VALIDATOR_SET_CYCLE: u64 = 86400000000000;
 
 // Structs in Rust are similar to other languages, and may include impl keyword as shown below
@@ -60,11 +72,35 @@ pub struct OctopusRelay {
     pub token_contract_id: AccountId,
     pub appchain_minimum_validators: u32,
     pub minimum_staking_amount: Balance,
+    /// Minimum amount of OCT token of a delegation
+    pub minimum_delegation_amount: Balance,
     pub total_staked_balance: Balance,
+    /// Balance of OCT token deposited for seeding validators, not allocated yet
+    pub seed_deposit_balance: Balance,
 
     pub bridge_limit_ratio: u16, // 100 as 1%
     pub owner: AccountId,
     pub oct_token_price: u128, // 1_000_000 as 1usd
+    /// Decimals of OCT token
+    pub oct_decimals: u32,
+    /// Last update time of the price of OCT token
+    pub oct_token_price_updated_at: Timestamp,
+    /// Minimum interval between two price updates of a token, in nanoseconds
+    pub price_update_cooldown: u64,
+    /// Maximum number of messages can be executed in one `relay` call
+    pub max_messages_per_relay: u32,
+    /// Maximum length in bytes of the receiver on appchain of locked or burnt tokens
+    pub max_receiver_len: u32,
+    /// Whether new appchains can be registered
+    pub registration_open: bool,
+    /// Minimum duration an appchain must stay in staging before activation, in nanoseconds
+    pub min_staging_duration_nanos: u64,
+    /// Length of a cycle of validator sets of all appchains, in nanoseconds
+    pub validator_set_cycle: u64,
+    /// Whether staking actions of all appchains are paused, it is set by storage migration
+    pub staking_globally_paused: bool,
+    /// Whether all operations moving tokens are paused by the owner, in emergency
+    pub paused: bool,
 
     /// Array of appchain ids
     pub appchain_id_list: Vector<AppchainId>,
@@ -76,6 +112,11 @@ pub struct OctopusRelay {
     pub appchain_states: UnorderedMap<AppchainId, LazyOption<AppchainState>>,
     /// Collection of native token of all appchains
     pub appchain_native_tokens: UnorderedMap<AppchainId, AccountId>,
+    /// Allowlist of categories which can be tagged to appchains
+    pub appchain_categories: UnorderedMap<String, bool>,
+    /// Total value of bridge tokens locked in each appchain,
+    /// updated on every lock and unlock and on price updates of bridge tokens
+    pub bridge_used_values: LookupMap<AppchainId, Balance>,
 }
 
 #[ext_contract(ext_self)]
@@ -95,6 +136,33 @@ pub trait ExtOctopusRelay {
         &mut self,
         appchain_id: AppchainId,
         validator_id: ValidatorId,
+        refund_account: AccountId,
+        amount: U128,
+    );
+    fn resolve_undelegate(
+        &mut self,
+        appchain_id: AppchainId,
+        validator_id: ValidatorId,
+        delegator_id: DelegatorId,
+        amount: U128,
+    );
+    fn resolve_reduce_stake(
+        &mut self,
+        appchain_id: AppchainId,
+        validator_id: ValidatorId,
+        refund_account: AccountId,
+        amount: U128,
+    );
+    fn resolve_withdraw_vote(
+        &mut self,
+        appchain_id: AppchainId,
+        account_id: AccountId,
+        account_vote: AccountVote,
+    );
+    fn resolve_withdraw_unclaimed_balance(
+        &mut self,
+        appchain_id: AppchainId,
+        account_id: AccountId,
         amount: U128,
     );
     fn execute(
@@ -102,6 +170,7 @@ pub trait ExtOctopusRelay {
         messages: Vec<Message>,
         appchain_id: AppchainId,
         remaining_deposit: Balance,
+        relayer: AccountId,
     );
     fn unlock_token(
         &mut self,
@@ -118,6 +187,7 @@ pub trait ExtOctopusRelay {
         receiver_id: AccountId,
         amount: U128,
         message_nonce: u64,
+        relayer: AccountId,
     );
     fn resolve_unlock_token(
         &mut self,
@@ -126,13 +196,22 @@ pub trait ExtOctopusRelay {
         amount: U128,
         message_nonce: u64,
     );
-    fn resolve_mint_native_token(&mut self, appchain_id: AppchainId, message_nonce: u64);
+    fn resolve_mint_native_token(
+        &mut self,
+        appchain_id: AppchainId,
+        receiver_id: AccountId,
+        amount: U128,
+        message_nonce: u64,
+        refund_to: AccountId,
+        refund: Balance,
+    );
     fn resolve_bridge_token_storage_deposit(
         &mut self,
         deposit: u128,
         receiver_id: ValidAccountId,
         amount: U128,
         token_id: AccountId,
+        appchain_id: AppchainId,
     );
     fn check_bridge_token_storage_deposit(
         &mut self,
@@ -180,6 +259,7 @@ impl OctopusRelay {
         minimum_staking_amount: U128,
         bridge_limit_ratio: u16,
         oct_token_price: U128,
+        oct_decimals: Option<u32>,
     ) -> Self {
         assert!(!env::state_exists(), "The contract is already initialized");
         assert_self();
@@ -187,12 +267,25 @@ impl OctopusRelay {
             version: 0,
             token_contract_id,
             total_staked_balance: 0,
+            seed_deposit_balance: 0,
             appchain_minimum_validators,
             minimum_staking_amount: minimum_staking_amount.0,
+            minimum_delegation_amount: minimum_staking_amount.0
+                / DEFAULT_MINIMUM_DELEGATION_DIVISOR,
 
             owner: env::current_account_id(),
             bridge_limit_ratio,
             oct_token_price: oct_token_price.into(),
+            oct_decimals: oct_decimals.unwrap_or(DEFAULT_OCT_DECIMALS),
+            oct_token_price_updated_at: 0,
+            price_update_cooldown: 0,
+            max_messages_per_relay: DEFAULT_MAX_MESSAGES_PER_RELAY,
+            max_receiver_len: DEFAULT_MAX_RECEIVER_LEN,
+            registration_open: true,
+            min_staging_duration_nanos: 0,
+            validator_set_cycle: DEFAULT_VALIDATOR_SET_CYCLE,
+            staking_globally_paused: false,
+            paused: false,
 
             appchain_id_list: Vector::new(StorageKey::AppchainIdList.into_bytes()),
             bridge_tokens: UnorderedMap::new(StorageKey::BridgeTokens.into_bytes()),
@@ -201,6 +294,8 @@ impl OctopusRelay {
             appchain_native_tokens: UnorderedMap::new(
                 StorageKey::AppchainNativeTokens.into_bytes(),
             ),
+            appchain_categories: UnorderedMap::new(StorageKey::AppchainCategories.into_bytes()),
+            bridge_used_values: LookupMap::new(StorageKey::BridgeUsedValues.into_bytes()),
         }
     }
 
@@ -236,25 +331,65 @@ impl OctopusRelay {
                     &self.token_contract_id,
                     "Only supports the OCT token contract"
                 );
-                assert_eq!(msg_vec.len(), 7, "params length wrong!");
+                if !self.registration_open {
+                    log!("Registration of appchain is closed, the tokens are returned.");
+                    return PromiseOrValue::Value(amount);
+                }
+                assert!(
+                    msg_vec.len() >= 2 && msg_vec.len() <= 9,
+                    "params length wrong!"
+                );
+                if self.appchain_exists(msg_vec.get(1).unwrap()) {
+                    log!(
+                        "Appchain '{}' is already registered, the tokens are returned.",
+                        msg_vec.get(1).unwrap()
+                    );
+                    return PromiseOrValue::Value(amount);
+                }
+                // Fields of basic info are optional, they can be filled in by `update_appchain`
+                let info_field =
+                    |index: usize| msg_vec.get(index).map_or(String::new(), |f| f.to_string());
+                // The category tag is optional
+                let category = msg_vec
+                    .get(7)
+                    .filter(|c| !c.is_empty())
+                    .map(|c| c.to_string());
+                // The key type of validator ids is optional, `raw32` by default
+                let validator_key_type = msg_vec.get(8).map_or(KeyType::default(), |k| {
+                    KeyType::from_name(k).expect("Invalid key type of validator ids.")
+                });
                 self.register_appchain(
                     msg_vec.get(1).unwrap().to_string(),
-                    msg_vec.get(2).unwrap().to_string(),
-                    msg_vec.get(3).unwrap().to_string(),
-                    msg_vec.get(4).unwrap().to_string(),
-                    msg_vec.get(5).unwrap().to_string(),
-                    msg_vec.get(6).unwrap().to_string(),
+                    info_field(2),
+                    info_field(3),
+                    info_field(4),
+                    info_field(5),
+                    info_field(6),
                     amount.into(),
+                    category,
+                    validator_key_type,
                 );
                 PromiseOrValue::Value(0.into())
             }
             "stake" => {
+                self.assert_not_paused();
                 assert_eq!(
                     &env::predecessor_account_id(),
                     &self.token_contract_id,
                     "Only supports the OCT token contract"
                 );
                 assert_eq!(msg_vec.len(), 3, "params length wrong!");
+                if !self.appchain_exists(msg_vec.get(1).unwrap()) {
+                    log!(
+                        "Appchain '{}' doesn't exist, the tokens are returned.",
+                        msg_vec.get(1).unwrap()
+                    );
+                    return PromiseOrValue::Value(amount);
+                }
+                if self.staking_globally_paused {
+                    log!("Staking is paused, the tokens are returned.");
+                    return PromiseOrValue::Value(amount);
+                }
                 self.stake(
                     msg_vec.get(1).unwrap().to_string(),
                     msg_vec.get(2).unwrap().to_string(),
@@ -263,25 +398,121 @@ impl OctopusRelay {
                 PromiseOrValue::Value(0.into())
             }
             "stake_more" => {
+                self.assert_not_paused();
                 assert_eq!(
                     &env::predecessor_account_id(),
                     &self.token_contract_id,
                     "Only supports the OCT token contract"
                 );
                 assert_eq!(msg_vec.len(), 2, "params length wrong!");
+                if !self.appchain_exists(msg_vec.get(1).unwrap()) {
+                    log!(
+                        "Appchain '{}' doesn't exist, the tokens are returned.",
+                        msg_vec.get(1).unwrap()
+                    );
+                    return PromiseOrValue::Value(amount);
+                }
+                if self.staking_globally_paused {
+                    log!("Staking is paused, the tokens are returned.");
+                    return PromiseOrValue::Value(amount);
+                }
                 self.stake_more(msg_vec.get(1).unwrap().to_string(), amount.0);
                 PromiseOrValue::Value(0.into())
             }
+            "delegate" => {
+                self.assert_not_paused();
+                assert_eq!(
+                    &env::predecessor_account_id(),
+                    &self.token_contract_id,
+                    "Only supports the OCT token contract"
+                );
+                assert_eq!(msg_vec.len(), 3, "params length wrong!");
+                if !self.appchain_exists(msg_vec.get(1).unwrap()) {
+                    log!(
+                        "Appchain '{}' doesn't exist, the tokens are returned.",
+                        msg_vec.get(1).unwrap()
+                    );
+                    return PromiseOrValue::Value(amount);
+                }
+                if self.staking_globally_paused {
+                    log!("Staking is paused, the tokens are returned.");
+                    return PromiseOrValue::Value(amount);
+                }
+                self.delegate(
+                    msg_vec.get(1).unwrap().to_string(),
+                    msg_vec.get(2).unwrap().to_string(),
+                    amount.0,
+                );
+                PromiseOrValue::Value(0.into())
+            }
+            "vote" => {
+                assert_eq!(
+                    &env::predecessor_account_id(),
+                    &self.token_contract_id,
+                    "Only supports the OCT token contract"
+                );
+                assert_eq!(msg_vec.len(), 3, "params length wrong!");
+                if !self.appchain_exists(msg_vec.get(1).unwrap()) {
+                    log!(
+                        "Appchain '{}' doesn't exist, the tokens are returned.",
+                        msg_vec.get(1).unwrap()
+                    );
+                    return PromiseOrValue::Value(amount);
+                }
+                let vote = match msg_vec.get(2).unwrap().as_str() {
+                    "yes" => Vote::Yes,
+                    "no" => Vote::No,
+                    _ => panic!("The vote should be yes or no."),
+                };
+                self.vote(
+                    msg_vec.get(1).unwrap().to_string(),
+                    sender_id.into(),
+                    vote,
+                    amount.0,
+                );
+                PromiseOrValue::Value(0.into())
+            }
+            "seed_deposit" => {
+                assert_eq!(
+                    &env::predecessor_account_id(),
+                    &self.token_contract_id,
+                    "Only supports the OCT token contract"
+                );
+                assert_eq!(msg_vec.len(), 1, "params length wrong!");
+                self.seed_deposit_balance += amount.0;
+                log!(
+                    "Seed deposit received from @{}, amount is {}.",
+                    sender_id.as_ref(),
+                    amount.0
+                );
+                PromiseOrValue::Value(0.into())
+            }
             "lock_token" => {
+                self.assert_not_paused();
                 let token_id = env::predecessor_account_id();
                 assert_eq!(msg_vec.len(), 3, "params length wrong!");
-                self.lock_token(
+                if msg_vec.get(2).unwrap().len() > self.max_receiver_len as usize {
+                    log!(
+                        "The receiver is longer than {} bytes, the tokens are returned.",
+                        self.max_receiver_len
+                    );
+                    return PromiseOrValue::Value(amount);
+                }
+                let lock_result = self.lock_token(
                     msg_vec.get(1).unwrap().to_string(),
                     msg_vec.get(2).unwrap().to_string(),
                     sender_id.into(),
                     token_id,
                     amount.0,
                 );
+                match lock_result.seq_num {
+                    Some(seq_num) => log!(
+                        "Token locked, seq_num is {}, amount is {}.",
+                        seq_num,
+                        lock_result.amount.0
+                    ),
+                    None => log!("Token locked, amount is {}.", lock_result.amount.0),
+                }
                 PromiseOrValue::Value(0.into())
             }
             _ => {
@@ -307,6 +538,19 @@ impl OctopusRelay {
         hex_address
     }
 
+    fn validate_validator_id(&self, id: String, key_type: &KeyType) -> String {
+        let validator_id = self.validate_hex_address(id);
+        if *key_type != KeyType::Raw32 {
+            // All zero bytes encode the identity point, which is not a valid public key.
+            assert!(
+                validator_id[2..].chars().any(|c| c != '0'),
+                "The validator id is not a valid {:?} public key.",
+                key_type
+            );
+        }
+        validator_id
+    }
+
     fn register_appchain(
         &mut self,
         appchain_id: String,
@@ -316,12 +560,17 @@ impl OctopusRelay {
         commit_id: String,
         email: String,
         bond_tokens: u128,
+        category: Option<String>,
+        validator_key_type: KeyType,
     ) {
         let founder_id = env::signer_account_id();
         assert!(
             self.appchain_metadatas.get(&appchain_id).is_none(),
             "Appchain_id is already registered"
         );
+        if let Some(category) = category.as_ref() {
+            self.assert_category_allowed(category);
+        }
         self.appchain_id_list.push(&appchain_id);
 
         self.appchain_metadatas.insert(
@@ -330,29 +579,40 @@ impl OctopusRelay {
                 StorageKey::AppchainMetadata(appchain_id.clone()).into_bytes(),
                 Some(&AppchainMetadata::new(
                     appchain_id.clone(),
-                    founder_id,
+                    founder_id.clone(),
                     website_url,
                     github_address,
                     github_release,
                     commit_id,
                     email,
                     bond_tokens,
+                    category,
                 )),
             ),
         );
-        self.appchain_states.insert(
-            &appchain_id,
-            &LazyOption::new(
-                StorageKey::AppchainState(appchain_id.clone()).into_bytes(),
-                Some(&AppchainState::new(&appchain_id)),
-            ),
-        );
+        let mut appchain_state = AppchainState {
+            validator_key_type,
+            validator_set_cycle: self.validator_set_cycle,
+            ..AppchainState::new(&appchain_id)
+        };
 
         log!(
             "Appchain added, appchain_id is {}, bund_tokens is {}.",
             appchain_id,
             u128::from(bond_tokens)
         );
+        appchain_state.emit_event(RelayEvent::AppchainRegistered {
+            appchain_id: appchain_id.clone(),
+            founder_id,
+            bond_tokens: bond_tokens.into(),
+        });
+        self.appchain_states.insert(
+            &appchain_id,
+            &LazyOption::new(
+                StorageKey::AppchainState(appchain_id.clone()).into_bytes(),
+                Some(&appchain_state),
+            ),
+        );
     }
 
     fn get_appchain_metadata(&self, appchain_id: &AppchainId) -> AppchainMetadata {
@@ -374,6 +634,11 @@ impl OctopusRelay {
             .set(appchain_metadata);
     }
 
+    fn appchain_exists(&self, appchain_id: &AppchainId) -> bool {
+        self.appchain_metadatas.get(appchain_id).is_some()
+            && self.appchain_states.get(appchain_id).is_some()
+    }
+
     fn get_appchain_state(&self, appchain_id: &AppchainId) -> AppchainState {
         self.appchain_states
             .get(appchain_id)
@@ -389,13 +654,61 @@ impl OctopusRelay {
             .set(appchain_state);
     }
 
+    // Total value of bridge tokens locked in an appchain, computed from all bridge tokens
+    fn compute_bridge_used_val(&self, appchain_state: &AppchainState) -> Balance {
+        self.bridge_tokens
+            .values_as_vector()
+            .iter()
+            .map(|f| f.get().unwrap())
+            .map(|token| token.value_of(appchain_state.get_total_locked_amount_of(&token.id())))
+            .fold(0, |total: Balance, val| total.saturating_add(val))
+    }
+
+    // Update the total value of bridge tokens locked in an appchain,
+    // by the change of locked amount of a token
+    fn update_bridge_used_val(
+        &mut self,
+        appchain_id: &AppchainId,
+        token_id: &AccountId,
+        old_locked: Balance,
+        new_locked: Balance,
+    ) {
+        let bridge_token = self
+            .bridge_tokens
+            .get(token_id)
+            .and_then(|token| token.get())
+            .expect("Unregistered token id");
+        self.replace_bridge_used_val(
+            appchain_id,
+            bridge_token.value_of(old_locked),
+            bridge_token.value_of(new_locked),
+        );
+    }
+
+    // Replace a part of the total value of bridge tokens locked in an appchain
+    fn replace_bridge_used_val(
+        &mut self,
+        appchain_id: &AppchainId,
+        old_val: Balance,
+        new_val: Balance,
+    ) {
+        let used_val = self.bridge_used_values.get(appchain_id).unwrap_or(0);
+        let new_used_val = if new_val >= old_val {
+            used_val.saturating_add(new_val - old_val)
+        } else {
+            used_val.saturating_sub(old_val - new_val)
+        };
+        self.bridge_used_values.insert(appchain_id, &new_used_val);
+    }
+
+    /// Update basic info of an appchain, the github fields are empty if not given
     pub fn update_appchain(
         &mut self,
         appchain_id: AppchainId,
         website_url: String,
-        github_address: String,
-        github_release: String,
-        commit_id: String,
+        github_address: Option<String>,
+        github_release: Option<String>,
+        commit_id: Option<String>,
         email: String,
         rpc_endpoint: String,
     ) {
@@ -416,20 +729,178 @@ impl OctopusRelay {
 
         appchain_metadata.update_basic_info(
             website_url,
-            github_address,
-            github_release,
-            commit_id,
+            github_address.unwrap_or_default(),
+            github_release.unwrap_or_default(),
+            commit_id.unwrap_or_default(),
             email,
             rpc_endpoint,
         );
         self.set_appchain_metadata(&appchain_id, &appchain_metadata);
     }
 
+    /// Set or clear the category tag of an appchain, can only be called by the founder
+    pub fn set_appchain_category(&mut self, appchain_id: AppchainId, category: Option<String>) {
+        let mut appchain_metadata = self.get_appchain_metadata(&appchain_id);
+        assert!(
+            env::signer_account_id().eq(&appchain_metadata.founder_id),
+            "You aren't the appchain founder!"
+        );
+        if let Some(category) = category.as_ref() {
+            self.assert_category_allowed(category);
+        }
+        appchain_metadata.update_category(category);
+        self.set_appchain_metadata(&appchain_id, &appchain_metadata);
+    }
+
+    /// Set or clear the operational note of an appchain, can be called by the owner or the founder
+    pub fn set_appchain_note(&mut self, appchain_id: AppchainId, note: Option<String>) {
+        let mut appchain_metadata = self.get_appchain_metadata(&appchain_id);
+        assert!(
+            env::predecessor_account_id().eq(&self.owner)
+                || env::signer_account_id().eq(&appchain_metadata.founder_id),
+            "Only the owner or the appchain founder can set the note."
+        );
+        if let Some(note) = note.as_ref() {
+            assert!(
+                note.len() <= MAX_APPCHAIN_NOTE_LENGTH,
+                "The note should be at most {} bytes.",
+                MAX_APPCHAIN_NOTE_LENGTH
+            );
+        }
+        appchain_metadata.update_operational_note(note);
+        self.set_appchain_metadata(&appchain_id, &appchain_metadata);
+    }
+
+    /// Add a category to the allowlist of appchain categories
+    pub fn add_category(&mut self, category: String) {
+        self.assert_owner();
+        assert!(!category.is_empty(), "Category can't be empty.");
+        self.appchain_categories.insert(&category, &true);
+    }
+
+    /// Remove a category from the allowlist of appchain categories
+    ///
+    /// The appchains already tagged with the category are not affected.
+    pub fn remove_category(&mut self, category: String) {
+        self.assert_owner();
+        self.appchain_categories
+            .remove(&category)
+            .expect("Category is not in the allowlist.");
+    }
+
+    pub fn get_categories(&self) -> Vec<String> {
+        self.appchain_categories.keys().collect()
+    }
+
+    fn assert_category_allowed(&self, category: &String) {
+        assert!(
+            self.appchain_categories.get(category).is_some(),
+            "Category '{}' is not in the allowlist.",
+            category
+        );
+    }
+
+    /// Get ids of appchains, without loading the metadata or state of them
+    pub fn get_appchain_ids(&self, from_index: u32, limit: u32) -> Vec<AppchainId> {
+        let end = std::cmp::min(
+            from_index.saturating_add(limit),
+            self.appchain_id_list.len() as u32,
+        );
+        (from_index..end)
+            .map(|index| self.appchain_id_list.get(index as u64).unwrap())
+            .collect()
+    }
+
+    /// Get appchains tagged with the given category, `from_index` is the index among these appchains
+    ///
+    /// Only the metadata of each appchain is loaded for filtering, and the scan stops
+    /// once `from_index + limit` appchains are matched.
+    pub fn get_appchains_by_category(
+        &self,
+        category: String,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<Appchain> {
+        self.appchain_id_list
+            .iter()
+            .filter(|appchain_id| {
+                self.appchain_metadatas
+                    .get(appchain_id)
+                    .and_then(|appchain_metadata| appchain_metadata.get())
+                    .map_or(false, |appchain_metadata| {
+                        appchain_metadata.category.as_ref() == Some(&category)
+                    })
+            })
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .filter_map(|appchain_id| self.get_appchain(appchain_id))
+            .collect()
+    }
+
+    /// Get appchains in the given status, `from_index` is the index among these appchains
+    ///
+    /// Only the state of each appchain is loaded for filtering, and the scan stops
+    /// once `from_index + limit` appchains are matched.
+    pub fn get_appchains_by_status(
+        &self,
+        status: AppchainStatus,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<Appchain> {
+        self.appchain_id_list
+            .iter()
+            .filter(|appchain_id| {
+                self.appchain_states
+                    .get(appchain_id)
+                    .and_then(|appchain_state| appchain_state.get())
+                    .map_or(false, |appchain_state| appchain_state.status == status)
+            })
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .filter_map(|appchain_id| self.get_appchain(appchain_id))
+            .collect()
+    }
+
+    /// Whether a booting appchain has a pending update of validator set,
+    /// false for other or unknown appchains
+    pub fn is_validator_set_rotation_due(&self, appchain_id: AppchainId) -> bool {
+        self.appchain_states
+            .get(&appchain_id)
+            .and_then(|appchain_state| appchain_state.get())
+            .map_or(false, |appchain_state| {
+                appchain_state.status == AppchainStatus::Booting
+                    && appchain_state.should_next_validator_set()
+            })
+    }
+
+    /// Get ids of booting appchains which have a pending update of validator set
+    ///
+    /// The state of every appchain in range `from_index..from_index + limit` of the appchain list
+    /// is loaded, so `limit` is capped to `MAX_APPCHAINS_SCANNED_PER_VIEW`.
+    pub fn get_appchains_needing_rotation(&self, from_index: u32, limit: u32) -> Vec<AppchainId> {
+        let end = std::cmp::min(
+            from_index.saturating_add(std::cmp::min(limit, MAX_APPCHAINS_SCANNED_PER_VIEW)),
+            self.appchain_id_list.len() as u32,
+        );
+        (from_index..end)
+            .map(|index| self.appchain_id_list.get(index as u64).unwrap())
+            .filter(|appchain_id| {
+                let appchain_state = self.get_appchain_state(appchain_id);
+                appchain_state.status == AppchainStatus::Booting
+                    && appchain_state.should_next_validator_set()
+            })
+            .collect()
+    }
+
     pub fn get_appchains(&self, from_index: u32, limit: u32) -> Vec<Appchain> {
-        (from_index..std::cmp::min(from_index + limit, self.appchain_id_list.len() as u32))
-            .map(|index| {
+        let end = std::cmp::min(
+            from_index.saturating_add(limit),
+            self.appchain_id_list.len() as u32,
+        );
+        (from_index..end)
+            .filter_map(|index| {
                 let appchain_id = self.appchain_id_list.get(index as u64).unwrap();
-                self.get_appchain(appchain_id).unwrap()
+                self.get_appchain(appchain_id)
             })
             .collect()
     }
@@ -458,9 +929,80 @@ impl OctopusRelay {
         self.minimum_staking_amount.into()
     }
 
-    pub fn get_appchain(&self, appchain_id: AppchainId) -> Option<Appchain> {
-        let appchain_metadata = self.get_appchain_metadata(&appchain_id);
+    pub fn get_oct_decimals(&self) -> u32 {
+        self.oct_decimals
+    }
+
+    /// Get an overview of validators, stake and bridge utilization of an appchain
+    pub fn get_appchain_health(&self, appchain_id: AppchainId) -> AppchainHealth {
         let appchain_state = self.get_appchain_state(&appchain_id);
+        let validator_count = appchain_state.validators.len() as u32;
+        AppchainHealth {
+            validator_count,
+            meets_minimum: validator_count >= self.appchain_minimum_validators,
+            staked_balance: appchain_state.staked_balance.into(),
+            bridge_utilization_bps: self.get_bridge_utilization(appchain_id).utilization_bps,
+            status: appchain_state.status,
+        }
+    }
+
+    /// Get the booting info of an appchain, without loading the state of the appchain
+    pub fn get_appchain_booting_info(&self, appchain_id: AppchainId) -> Option<BootingInfo> {
+        self.appchain_metadatas
+            .get(&appchain_id)
+            .and_then(|metadata| metadata.get())
+            .and_then(|appchain_metadata| appchain_metadata.to_booting_info())
+    }
+
+    /// Get the chain spec of an appchain, without loading the state of the appchain
+    pub fn get_appchain_chain_spec(&self, appchain_id: AppchainId) -> Option<ChainSpecInfo> {
+        self.appchain_metadatas
+            .get(&appchain_id)
+            .and_then(|metadata| metadata.get())
+            .and_then(|appchain_metadata| appchain_metadata.to_chain_spec_info())
+    }
+
+    /// Compare `total_staked_balance` with the sum of `staked_balance` of all appchains
+    ///
+    /// Only the first `MAX_APPCHAINS_SCANNED_PER_VIEW` appchains of the appchain list are summed,
+    /// so the delta is only meaningful while the number of appchains is within this cap.
+    pub fn get_staking_balance_delta(&self) -> StakingDelta {
+        let appchains_sum: u128 = self
+            .appchain_id_list
+            .iter()
+            .take(MAX_APPCHAINS_SCANNED_PER_VIEW as usize)
+            .filter_map(|appchain_id| self.appchain_states.get(&appchain_id))
+            .filter_map(|state| state.get())
+            .map(|appchain_state| appchain_state.staked_balance)
+            .sum();
+        StakingDelta {
+            relay_total: self.total_staked_balance.into(),
+            appchains_sum: appchains_sum.into(),
+            delta: (self.total_staked_balance as i128 - appchains_sum as i128).into(),
+        }
+    }
+
+    /// Get information of an appchain, `None` if the metadata or state of it is missing
+    pub fn get_appchain(&self, appchain_id: AppchainId) -> Option<Appchain> {
+        let appchain_metadata_option = self
+            .appchain_metadatas
+            .get(&appchain_id)
+            .and_then(|metadata| metadata.get());
+        let appchain_state_option = self
+            .appchain_states
+            .get(&appchain_id)
+            .and_then(|state| state.get());
+        let (appchain_metadata, appchain_state) =
+            match (appchain_metadata_option, appchain_state_option) {
+                (Some(appchain_metadata), Some(appchain_state)) => {
+                    (appchain_metadata, appchain_state)
+                }
+                (None, None) => return None,
+                _ => {
+                    log!("Warning: data of appchain '{}' is incomplete.", appchain_id);
+                    return None;
+                }
+            };
         Some(Appchain {
             id: appchain_id.clone(),
             founder_id: appchain_metadata.founder_id.clone(),
@@ -482,7 +1024,10 @@ impl OctopusRelay {
             block_height: appchain_metadata.block_height,
             staked_balance: appchain_state.staked_balance.into(),
             subql_url: appchain_metadata.subql_url.clone(),
-            fact_sets_len: appchain_state.raw_facts.len().try_into().unwrap_or(0),
+            fact_sets_len: seq_num_from_len(appchain_state.raw_facts.len()),
+            category: appchain_metadata.category.clone(),
+            operational_note: appchain_metadata.operational_note.clone(),
+            validator_key_type: appchain_state.validator_key_type.clone(),
         })
     }
 
@@ -490,24 +1035,177 @@ impl OctopusRelay {
         self.version
     }
 
+    /// Get all tunable parameters of the relay contract
+    pub fn get_relay_config(&self) -> RelayConfig {
+        RelayConfig {
+            version: self.version,
+            token_contract_id: self.token_contract_id.clone(),
+            owner: self.owner.clone(),
+            appchain_minimum_validators: self.appchain_minimum_validators,
+            minimum_staking_amount: self.minimum_staking_amount.into(),
+            minimum_delegation_amount: self.minimum_delegation_amount.into(),
+            bridge_limit_ratio: self.bridge_limit_ratio,
+            oct_token_price: self.oct_token_price.into(),
+            price_update_cooldown: self.price_update_cooldown.into(),
+            max_messages_per_relay: self.max_messages_per_relay,
+            max_receiver_len: self.max_receiver_len,
+            registration_open: self.registration_open,
+            min_staging_duration_nanos: self.min_staging_duration_nanos.into(),
+            validator_set_cycle: self.validator_set_cycle.into(),
+            staking_globally_paused: self.staking_globally_paused,
+            paused: self.paused,
+        }
+    }
+
+    pub fn set_max_messages_per_relay(&mut self, max_messages_per_relay: u32) {
+        self.assert_owner();
+        assert!(
+            max_messages_per_relay > 0,
+            "The maximum number of messages should be greater than 0."
+        );
+        self.max_messages_per_relay = max_messages_per_relay;
+    }
+
+    pub fn get_max_messages_per_relay(&self) -> u32 {
+        self.max_messages_per_relay
+    }
+
+    /// Set the maximum length in bytes of the receiver on appchain of locked or burnt tokens
+    pub fn set_max_receiver_len(&mut self, max_receiver_len: u32) {
+        self.assert_owner();
+        assert!(
+            max_receiver_len > 0,
+            "The maximum length of receiver should be greater than 0."
+        );
+        self.max_receiver_len = max_receiver_len;
+    }
+
+    pub fn get_max_receiver_len(&self) -> u32 {
+        self.max_receiver_len
+    }
+
+    /// Open or close the registration of new appchains, existing appchains are not affected
+    pub fn set_registration_open(&mut self, open: bool) {
+        self.assert_owner();
+        self.registration_open = open;
+    }
+
+    pub fn is_registration_open(&self) -> bool {
+        self.registration_open
+    }
+
+    /// Resume staking actions which were paused by storage migration
+    pub fn resume_staking_after_migration(&mut self) {
+        self.assert_owner();
+        assert!(self.staking_globally_paused, "Staking is not paused.");
+        self.staking_globally_paused = false;
+    }
+
+    pub fn is_staking_globally_paused(&self) -> bool {
+        self.staking_globally_paused
+    }
+
+    /// Pause staking, bridging and relaying of all appchains, views are still available
+    pub fn pause(&mut self) {
+        self.assert_owner();
+        assert!(!self.paused, "The contract is already paused.");
+        self.paused = true;
+        log!("The contract is paused.");
+    }
+
+    pub fn unpause(&mut self) {
+        self.assert_owner();
+        assert!(self.paused, "The contract is not paused.");
+        self.paused = false;
+        log!("The contract is unpaused.");
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    fn assert_not_paused(&self) {
+        assert!(!self.paused, "The contract is paused.");
+    }
+
+    /// Set the minimum duration an appchain must stay in staging before activation, in nanoseconds
+    pub fn set_min_staging_duration(&mut self, duration: U64) {
+        self.assert_owner();
+        self.min_staging_duration_nanos = duration.into();
+    }
+
+    pub fn get_min_staging_duration(&self) -> U64 {
+        self.min_staging_duration_nanos.into()
+    }
+
+    /// Set the length of a cycle of validator sets of appchains registered later, in nanoseconds
+    ///
+    /// The existing appchains keep their cycles, since their validator sets are counted
+    /// from the booting time by the cycles.
+    pub fn set_validator_set_cycle(&mut self, nanoseconds: U128) {
+        self.assert_owner();
+        let validator_set_cycle =
+            u64::try_from(nanoseconds.0).expect("The validator set cycle is too long.");
+        assert!(
+            validator_set_cycle > 0,
+            "The validator set cycle should be greater than 0."
+        );
+        self.validator_set_cycle = validator_set_cycle;
+    }
+
+    pub fn get_validator_set_cycle(&self) -> U64 {
+        self.validator_set_cycle.into()
+    }
+
     pub fn get_appchain_minimum_validators(&self) -> u32 {
         self.appchain_minimum_validators
     }
 
+    /// Set the minimum amount of OCT token to stake as a validator,
+    /// it doesn't affect the existing stake
+    pub fn set_minimum_staking_amount(&mut self, amount: U128) {
+        self.assert_owner();
+        assert!(
+            amount.0 > 0,
+            "The minimum staking amount should be greater than 0."
+        );
+        self.minimum_staking_amount = amount.into();
+    }
+
+    /// Set the minimum amount of OCT token of a delegation,
+    /// it doesn't affect the existing delegations
+    pub fn set_minimum_delegation_amount(&mut self, amount: U128) {
+        self.assert_owner();
+        self.minimum_delegation_amount = amount.into();
+    }
+
+    pub fn get_minimum_delegation_amount(&self) -> U128 {
+        self.minimum_delegation_amount.into()
+    }
+
+    /// Set the minimum number of validators to activate an appchain,
+    /// the appchains which are already booting are not affected
+    pub fn set_appchain_minimum_validators(&mut self, n: u32) {
+        self.assert_owner();
+        assert!(
+            n > 0,
+            "The minimum number of validators should be greater than 0."
+        );
+        self.appchain_minimum_validators = n;
+    }
+
+    /// Get current validators of an appchain in range `from_index..from_index + limit`
     pub fn get_validators(
         &self,
         appchain_id: AppchainId,
-        start: u32,
+        from_index: u32,
         limit: u32,
-    ) -> Option<Vec<Validator>> {
-        let appchain_state = self.get_appchain_state(&appchain_id);
-        Option::from(
-            appchain_state
-                .get_validators(start, limit)
-                .iter()
-                .map(|v| v.to_validator())
-                .collect::<Vec<_>>(),
-        )
+    ) -> Vec<Validator> {
+        self.get_appchain_state(&appchain_id)
+            .get_validators(from_index, limit)
+            .iter()
+            .map(|v| v.to_validator())
+            .collect()
     }
 
     pub fn account_exists(&self, appchain_id: AppchainId, account_id: AccountId) -> Option<bool> {
@@ -515,6 +1213,33 @@ impl OctopusRelay {
         Option::from(appchain_state.account_exists(&account_id))
     }
 
+    /// Get vote amounts of an account to an appchain, `None` if the account hasn't voted
+    pub fn get_account_vote(
+        &self,
+        appchain_id: AppchainId,
+        account_id: AccountId,
+    ) -> Option<AccountVote> {
+        self.get_appchain_state(&appchain_id)
+            .get_account_vote(&account_id)
+    }
+
+    /// Get OCT tokens of an account which are no longer staked or delegated in an appchain,
+    /// such as the delegations to a removed validator
+    pub fn get_unclaimed_balance(&self, appchain_id: AppchainId, account_id: AccountId) -> U128 {
+        self.get_appchain_state(&appchain_id)
+            .get_unclaimed_balance(&account_id)
+            .into()
+    }
+
+    /// Get total upvote and downvote balance of an appchain
+    pub fn get_appchain_votes(&self, appchain_id: AppchainId) -> (U128, U128) {
+        let appchain_state = self.get_appchain_state(&appchain_id);
+        (
+            appchain_state.upvote_balance.into(),
+            appchain_state.downvote_balance.into(),
+        )
+    }
+
     pub fn next_validator_set(
         &self,
         appchain_id: AppchainId,
@@ -555,6 +1280,61 @@ impl OctopusRelay {
         Option::None
     }
 
+    /// Whether a validator would be included in the next validator set of an appchain,
+    /// false for unknown validators
+    pub fn will_be_in_next_set(&self, appchain_id: AppchainId, validator_id: ValidatorId) -> bool {
+        self.get_appchain_state(&appchain_id)
+            .is_in_next_validator_set(&validator_id)
+    }
+
+    /// Get the set_id of the validator set of an appchain which was in effect at `timestamp`
+    pub fn set_id_for_timestamp(&self, appchain_id: AppchainId, timestamp: U64) -> Option<SetId> {
+        self.get_appchain_state(&appchain_id)
+            .set_id_for_timestamp(timestamp.into())
+    }
+
+    /// Get validators joined or left an appchain since `since_ts`,
+    /// compared between the validator sets in effect at `since_ts` and the latest one
+    pub fn get_validator_changes_since(
+        &self,
+        appchain_id: AppchainId,
+        since_ts: U64,
+    ) -> ValidatorChanges {
+        self.get_appchain_state(&appchain_id)
+            .get_validator_changes_since(since_ts.into())
+    }
+
+    /// Get validator of an account in each of the given appchains,
+    /// `None` if the account isn't a validator of the appchain
+    pub fn get_validators_for_account(
+        &self,
+        account_id: AccountId,
+        appchain_ids: Vec<AppchainId>,
+    ) -> Vec<(AppchainId, Option<Validator>)> {
+        assert!(
+            appchain_ids.len() <= MAX_APPCHAINS_SCANNED_PER_VIEW as usize,
+            "Too many appchains, the maximum is {}.",
+            MAX_APPCHAINS_SCANNED_PER_VIEW
+        );
+        appchain_ids
+            .into_iter()
+            .map(|appchain_id| {
+                let validator = self
+                    .appchain_states
+                    .get(&appchain_id)
+                    .and_then(|state_option| state_option.get())
+                    .and_then(|appchain_state| {
+                        appchain_state
+                            .account_map
+                            .get(&account_id)
+                            .and_then(|validator_id| appchain_state.get_validator(&validator_id))
+                    })
+                    .map(|appchain_validator| appchain_validator.to_validator());
+                (appchain_id, validator)
+            })
+            .collect()
+    }
+
     pub fn get_delegator(
         &self,
         appchain_id: AppchainId,
@@ -575,6 +1355,34 @@ impl OctopusRelay {
         Option::None
     }
 
+    /// Get delegators of a validator in range `from_index..from_index + limit`
+    pub fn get_delegators(
+        &self,
+        appchain_id: AppchainId,
+        validator_id: ValidatorId,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<Delegator> {
+        let appchain_validator = self
+            .get_appchain_state(&appchain_id)
+            .get_validator(&validator_id)
+            .expect("This validator not exists");
+        let delegators = appchain_validator.delegators.values_as_vector();
+        let end = std::cmp::min(from_index.saturating_add(limit), delegators.len() as u32);
+        (from_index..end)
+            .filter_map(|index| delegators.get(index as u64).unwrap().get())
+            .map(|appchain_delegator| appchain_delegator.to_delegator())
+            .collect()
+    }
+
+    pub fn get_num_delegators(&self, appchain_id: AppchainId, validator_id: ValidatorId) -> u32 {
+        self.get_appchain_state(&appchain_id)
+            .get_validator(&validator_id)
+            .expect("This validator not exists")
+            .delegators
+            .len() as u32
+    }
+
     pub fn get_validator_set(&self, appchain_id: AppchainId) -> Option<ValidatorSet> {
         if let Some(state_option) = self.appchain_states.get(&appchain_id) {
             if let Some(appchain_state) = state_option.get() {
@@ -584,6 +1392,16 @@ impl OctopusRelay {
         Option::None
     }
 
+    /// Get the latest created validator set and the next one (if it's time to update) together
+    pub fn get_current_and_next_validator_set(&self, appchain_id: AppchainId) -> ValidatorSetPair {
+        let appchain_state = self.get_appchain_state(&appchain_id);
+        ValidatorSetPair {
+            current: appchain_state
+                .get_validator_set_by_nonce(&(appchain_state.validators_nonce - 1)),
+            next: appchain_state.get_next_validator_set(),
+        }
+    }
+
     pub fn get_validator_set_by_set_id(
         &self,
         appchain_id: AppchainId,
@@ -593,6 +1411,48 @@ impl OctopusRelay {
             .get_validator_set_by_nonce(&set_id)
     }
 
+    /// Get validators in a validator set sorted by weight descending, ties broken by id
+    pub fn get_validator_set_ranked(
+        &self,
+        appchain_id: AppchainId,
+        set_id: SetId,
+        from_index: u32,
+        limit: u32,
+    ) -> Vec<LiteValidator> {
+        self.get_appchain_state(&appchain_id)
+            .get_ranked_validators_of_set(&set_id)
+            .unwrap_or_default()
+            .into_iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    /// Get the range of set_id of validator sets which can be queried by `get_validator_set_by_set_id`
+    pub fn get_validator_set_range(&self, appchain_id: AppchainId) -> (SetId, SetId) {
+        self.get_appchain_state(&appchain_id)
+            .get_validator_set_range()
+    }
+
+    /// Get total staked balance of an appchain when a validator set was created
+    pub fn get_staked_balance_at_set(&self, appchain_id: AppchainId, set_id: u32) -> Option<U128> {
+        self.get_appchain_state(&appchain_id)
+            .get_staked_balance_at_set(&set_id)
+            .map(|balance| balance.into())
+    }
+
+    /// Get SCALE encoded validator set for consumption of the appchain runtime
+    pub fn get_validator_set_scale_encoded(
+        &self,
+        appchain_id: AppchainId,
+        set_id: u32,
+    ) -> Option<Base64VecU8> {
+        self.get_appchain_state(&appchain_id)
+            .get_validator_set_scale_encoded(&set_id)
+            .map(|encoded| encoded.into())
+    }
+
+    // Staking is closed for a frozen appchain
     fn in_staking_period(&mut self, appchain_id: AppchainId) -> bool {
         let required_status_vec = vec![AppchainStatus::Staging, AppchainStatus::Booting];
         required_status_vec
@@ -601,9 +1461,6 @@ impl OctopusRelay {
     }
 
     fn stake(&mut self, appchain_id: AppchainId, id: String, amount: u128) {
-        // Check to update validator set before all
-        let validator_id = self.validate_hex_address(id);
-
         assert!(
             self.in_staking_period(appchain_id.clone()),
             "It's not in staking period."
@@ -615,9 +1472,20 @@ impl OctopusRelay {
             "Insufficient staking amount"
         );
         let mut appchain_state = self.get_appchain_state(&appchain_id);
+        let validator_id = self.validate_validator_id(id, &appchain_state.validator_key_type);
         appchain_state.assert_validator_is_not_registered(&validator_id, &account_id);
+        assert!(
+            !appchain_state.is_validators_full(),
+            "The number of validators has reached the maximum."
+        );
         appchain_state.stake(&validator_id, &amount);
         self.total_staked_balance += amount;
+        appchain_state.emit_event(RelayEvent::ValidatorStaked {
+            appchain_id: appchain_id.clone(),
+            validator_id,
+            account_id,
+            amount: amount.into(),
+        });
         self.set_appchain_state(&appchain_id, &appchain_state);
     }
 
@@ -636,6 +1504,160 @@ impl OctopusRelay {
         self.set_appchain_state(&appchain_id, &appchain_state);
     }
 
+    // The account of the delegator is used as its delegator id
+    fn delegate(&mut self, appchain_id: AppchainId, validator_id: ValidatorId, amount: u128) {
+        assert!(
+            self.in_staking_period(appchain_id.clone()),
+            "It's not in staking period."
+        );
+        assert!(
+            amount >= self.minimum_delegation_amount,
+            "Insufficient delegation amount"
+        );
+        let validator_id = self.validate_hex_address(validator_id);
+        let account_id = env::signer_account_id();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.delegate(&validator_id, &account_id, &account_id, amount);
+        self.total_staked_balance += amount;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    fn vote(&mut self, appchain_id: AppchainId, account_id: AccountId, vote: Vote, amount: u128) {
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        assert_eq!(
+            appchain_state.status,
+            AppchainStatus::Voting,
+            "Appchain can't be voted in current status."
+        );
+        appchain_state.vote(&account_id, vote, amount);
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    /// Set the maximum number of validators of an appchain, 0 means unlimited
+    pub fn set_max_validators(&mut self, appchain_id: AppchainId, max_validators: u32) {
+        self.assert_owner();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.max_validators = max_validators;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    /// Set whether facts of locking and burning assets of an appchain are stored,
+    /// if not, they are only emitted as events, and locked tokens of the appchain
+    /// can't be audited or repaired from facts any more
+    pub fn set_persist_asset_facts(&mut self, appchain_id: AppchainId, persist_asset_facts: bool) {
+        self.assert_owner();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.persist_asset_facts = persist_asset_facts;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    pub fn get_persist_asset_facts(&self, appchain_id: AppchainId) -> bool {
+        self.get_appchain_state(&appchain_id).persist_asset_facts
+    }
+
+    /// Correct the staked amount of a validator,
+    /// the staked balance of the appchain and the total staked balance are reconciled
+    pub fn admin_adjust_validator_stake(
+        &mut self,
+        appchain_id: AppchainId,
+        validator_id: ValidatorId,
+        new_amount: U128,
+    ) {
+        self.assert_owner();
+        assert!(
+            new_amount.0 >= self.minimum_staking_amount,
+            "Insufficient staking amount, remove the validator instead."
+        );
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        let old_amount = appchain_state.adjust_validator_stake(&validator_id, new_amount.0);
+        assert!(
+            self.total_staked_balance + new_amount.0 >= old_amount,
+            "The total staked balance would be negative."
+        );
+        self.total_staked_balance = self.total_staked_balance + new_amount.0 - old_amount;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+        log!(
+            "Staked amount of validator '{}' of appchain '{}' is adjusted from {} to {}.",
+            validator_id,
+            appchain_id,
+            old_amount,
+            new_amount.0
+        );
+    }
+
+    /// Set the maximum ratio of delegated balance to self stake of validators
+    /// of an appchain in percent, 0 means unlimited
+    pub fn set_max_delegation_ratio(&mut self, appchain_id: AppchainId, max_delegation_ratio: u16) {
+        self.assert_owner();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.max_delegation_ratio = max_delegation_ratio;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    pub fn get_max_delegation_ratio(&self, appchain_id: AppchainId) -> u16 {
+        self.get_appchain_state(&appchain_id).max_delegation_ratio
+    }
+
+    /// Get ratio of delegated balance to self stake of a validator in percent
+    pub fn get_delegation_ratio(&self, appchain_id: AppchainId, validator_id: ValidatorId) -> u16 {
+        self.get_appchain_state(&appchain_id)
+            .get_validator(&validator_id)
+            .expect("This validator not exists")
+            .get_delegation_ratio()
+    }
+
+    /// Enable or disable a validator without removing its stake,
+    /// a disabled validator is excluded from the next validator set
+    pub fn set_validator_enabled(
+        &mut self,
+        appchain_id: AppchainId,
+        validator_id: ValidatorId,
+        enabled: bool,
+    ) {
+        self.assert_owner();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.set_validator_enabled(&validator_id, enabled);
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    /// Register validators directly for bootstrapping a staging appchain.
+    ///
+    /// The OCT tokens of the validators must be deposited beforehand,
+    /// through `ft_transfer_call` with message `seed_deposit`.
+    pub fn admin_seed_validators(&mut self, appchain_id: AppchainId, entries: Vec<SeedValidator>) {
+        self.assert_owner();
+        let total_amount: Balance = entries.iter().map(|e| e.amount.0).sum();
+        assert!(
+            total_amount <= self.seed_deposit_balance,
+            "Insufficient seed deposit balance."
+        );
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        for entry in entries {
+            let validator_id =
+                self.validate_validator_id(entry.validator_id, &appchain_state.validator_key_type);
+            assert!(
+                entry.amount.0 >= self.minimum_staking_amount,
+                "Insufficient staking amount"
+            );
+            assert!(
+                !appchain_state.is_validators_full(),
+                "The number of validators has reached the maximum."
+            );
+            appchain_state.seed_validator(
+                &validator_id,
+                entry.account_id.as_ref(),
+                &entry.amount.0,
+            );
+        }
+        self.seed_deposit_balance -= total_amount;
+        self.total_staked_balance += total_amount;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    pub fn get_seed_deposit_balance(&self) -> U128 {
+        self.seed_deposit_balance.into()
+    }
+
     pub fn remove_validator(&mut self, appchain_id: AppchainId, validator_id: String) {
         self.assert_owner();
         assert!(
@@ -646,11 +1668,15 @@ impl OctopusRelay {
         let validator = self
             .get_validator(appchain_id.clone(), validator_id.clone())
             .expect("This validator not exists");
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        // The validator is taken out before the transfer, so its stake can't be refunded twice
+        self.total_staked_balance -= appchain_state.take_validator(&validator_id);
+        self.set_appchain_state(&appchain_id, &appchain_state);
 
-        let account_id = validator.account_id;
+        let refund_account = validator.refund_account.unwrap_or(validator.account_id);
 
         ext_token::ft_transfer(
-            account_id.clone(),
+            refund_account.clone(),
             validator.staked_amount.into(),
             None,
             &self.token_contract_id,
@@ -660,6 +1686,7 @@ impl OctopusRelay {
         .then(ext_self::resolve_remove_validator(
             appchain_id,
             validator_id,
+            refund_account,
             validator.staked_amount.into(),
             &env::current_account_id(),
             NO_DEPOSIT,
@@ -667,26 +1694,89 @@ impl OctopusRelay {
         ));
     }
 
+    /// Retry a removal of validator whose refund transfer was failed,
+    /// the record is removed if the removal succeeds
+    pub fn retry_remove_validator(&mut self, appchain_id: AppchainId, validator_id: ValidatorId) {
+        assert!(
+            self.get_appchain_state(&appchain_id)
+                .failed_removals
+                .get(&validator_id)
+                .is_some(),
+            "Failed removal not found."
+        );
+        self.remove_validator(appchain_id, validator_id);
+    }
+
+    /// Get removals of validators whose refund transfer was failed
+    pub fn get_failed_removals(&self, appchain_id: AppchainId) -> Vec<FailedRemoval> {
+        self.get_appchain_state(&appchain_id)
+            .failed_removals
+            .values()
+            .collect()
+    }
+
     pub fn resolve_remove_validator(
         &mut self,
         appchain_id: AppchainId,
         validator_id: ValidatorId,
+        refund_account: AccountId,
         amount: U128,
     ) {
         assert_self();
         // Update state
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
             PromiseResult::Successful(_) => {
-                let mut appchain_state = self.get_appchain_state(&appchain_id);
-                self.total_staked_balance -= appchain_state.remove_validator(&validator_id);
-                self.set_appchain_state(&appchain_id, &appchain_state);
+                appchain_state.drop_removed_validator(&validator_id);
+                appchain_state.failed_removals.remove(&validator_id);
+                appchain_state.emit_event(RelayEvent::ValidatorRemoved {
+                    appchain_id: appchain_id.clone(),
+                    validator_id,
+                    refund_account,
+                    amount,
+                });
+            }
+            PromiseResult::Failed => {
+                // The validator is put back, the removal can be retried.
+                log!(
+                    "Failed to remove validator '{}', refund account is '{}', amount is {}.",
+                    validator_id,
+                    refund_account,
+                    amount.0
+                );
+                self.total_staked_balance += appchain_state.restore_validator(&validator_id);
+                appchain_state.failed_removals.insert(
+                    &validator_id,
+                    &FailedRemoval {
+                        validator_id: validator_id.clone(),
+                        refund_account,
+                        amount,
+                    },
+                );
             }
-            PromiseResult::Failed => {}
         }
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    /// Set the account to receive refunds of the stake of the validator of the caller,
+    /// `None` means the account of the validator
+    pub fn set_validator_refund_account(
+        &mut self,
+        appchain_id: AppchainId,
+        account: Option<ValidAccountId>,
+    ) {
+        let validator = self
+            .get_validator_by_account(appchain_id.clone(), env::predecessor_account_id())
+            .expect("You are not staked on the appchain");
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.set_validator_refund_account(&validator.id, account.map(|a| a.into()));
+        self.set_appchain_state(&appchain_id, &appchain_state);
     }
 
     pub fn unstake(&mut self, appchain_id: AppchainId) {
+        self.assert_not_paused();
+        assert!(!self.staking_globally_paused, "Staking is paused.");
         assert!(
             self.in_staking_period(appchain_id.clone()),
             "Appchain can't be staked in current status."
@@ -695,9 +1785,14 @@ impl OctopusRelay {
         let validator = self
             .get_validator_by_account(appchain_id.clone(), account_id.clone())
             .expect("You are not staked on the appchain");
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        // The validator is taken out before the transfer, so its stake can't be refunded twice
+        self.total_staked_balance -= appchain_state.take_validator(&validator.id);
+        self.set_appchain_state(&appchain_id, &appchain_state);
+        let refund_account = validator.refund_account.unwrap_or(account_id);
 
         ext_token::ft_transfer(
-            account_id.clone(),
+            refund_account.clone(),
             validator.staked_amount.into(),
             None,
             &self.token_contract_id,
@@ -707,6 +1802,7 @@ impl OctopusRelay {
         .then(ext_self::resolve_remove_validator(
             appchain_id,
             validator.id.clone(),
+            refund_account,
             validator.staked_amount.into(),
             &env::current_account_id(),
             NO_DEPOSIT,
@@ -714,9 +1810,279 @@ impl OctopusRelay {
         ));
     }
 
+    /// Withdraw part of the stake of the validator of the caller, staying in the validator set
+    pub fn reduce_stake(&mut self, appchain_id: AppchainId, amount: U128) {
+        self.assert_not_paused();
+        assert!(!self.staking_globally_paused, "Staking is paused.");
+        assert!(
+            self.in_staking_period(appchain_id.clone()),
+            "Appchain can't be staked in current status."
+        );
+        let account_id = env::signer_account_id();
+        let validator = self
+            .get_validator_by_account(appchain_id.clone(), account_id.clone())
+            .expect("You are not staked on the appchain");
+        self.get_appchain_state(&appchain_id)
+            .assert_stake_reducible(&validator.id, amount.0, self.minimum_staking_amount);
+        let refund_account = validator.refund_account.unwrap_or(account_id);
+        // The stake is deducted before the transfer, so it can't be withdrawn twice
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.reduce_stake(&validator.id, amount.0);
+        self.total_staked_balance -= amount.0;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+
+        ext_token::ft_transfer(
+            refund_account.clone(),
+            amount,
+            None,
+            &self.token_contract_id,
+            1,
+            GAS_FOR_FT_TRANSFER_CALL,
+        )
+        .then(ext_self::resolve_reduce_stake(
+            appchain_id,
+            validator.id,
+            refund_account,
+            amount,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            env::prepaid_gas() / 2,
+        ));
+    }
+
+    pub fn resolve_reduce_stake(
+        &mut self,
+        appchain_id: AppchainId,
+        validator_id: ValidatorId,
+        refund_account: AccountId,
+        amount: U128,
+    ) {
+        assert_self();
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                // Nothing is transferred, the stake is restored.
+                log!(
+                    "Failed to reduce stake of validator '{}', amount is {}.",
+                    validator_id,
+                    amount.0
+                );
+                let mut appchain_state = self.get_appchain_state(&appchain_id);
+                if appchain_state.restore_stake(&validator_id, &refund_account, amount.0) {
+                    self.total_staked_balance += amount.0;
+                } else {
+                    log!(
+                        "Validator '{}' is removed, the stake is moved to the unclaimed balance of '{}'.",
+                        validator_id,
+                        refund_account
+                    );
+                }
+                self.set_appchain_state(&appchain_id, &appchain_state);
+            }
+        }
+    }
+
+    /// Withdraw all balance delegated to a validator by the caller
+    pub fn undelegate(&mut self, appchain_id: AppchainId, validator_id: ValidatorId) {
+        self.assert_not_paused();
+        assert!(!self.staking_globally_paused, "Staking is paused.");
+        assert!(
+            self.in_staking_period(appchain_id.clone()),
+            "Appchain can't be staked in current status."
+        );
+        let account_id = env::signer_account_id();
+        let delegator = self
+            .get_delegator(
+                appchain_id.clone(),
+                validator_id.clone(),
+                account_id.clone(),
+            )
+            .expect("You are not delegating to the validator");
+        // The delegation is removed before the transfer, so it can't be withdrawn twice
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.undelegate(&validator_id, &account_id, delegator.amount.0);
+        self.total_staked_balance -= delegator.amount.0;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+
+        ext_token::ft_transfer(
+            account_id.clone(),
+            delegator.amount,
+            None,
+            &self.token_contract_id,
+            1,
+            GAS_FOR_FT_TRANSFER_CALL,
+        )
+        .then(ext_self::resolve_undelegate(
+            appchain_id,
+            validator_id,
+            account_id,
+            delegator.amount,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            env::prepaid_gas() / 2,
+        ));
+    }
+
+    pub fn resolve_undelegate(
+        &mut self,
+        appchain_id: AppchainId,
+        validator_id: ValidatorId,
+        delegator_id: DelegatorId,
+        amount: U128,
+    ) {
+        assert_self();
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                // Nothing is transferred, the delegation is restored.
+                log!(
+                    "Failed to undelegate '{}' from validator '{}', amount is {}.",
+                    delegator_id,
+                    validator_id,
+                    amount.0
+                );
+                let mut appchain_state = self.get_appchain_state(&appchain_id);
+                if appchain_state.restore_delegation(
+                    &validator_id,
+                    &delegator_id,
+                    &delegator_id,
+                    amount.0,
+                ) {
+                    self.total_staked_balance += amount.0;
+                } else {
+                    log!(
+                        "Validator '{}' is removed, the delegation of '{}' is moved to the unclaimed balance.",
+                        validator_id,
+                        delegator_id
+                    );
+                }
+                self.set_appchain_state(&appchain_id, &appchain_state);
+            }
+        }
+    }
+
+    /// Withdraw OCT tokens voted to an appchain by the caller, after the voting is concluded
+    pub fn withdraw_vote(&mut self, appchain_id: AppchainId) {
+        self.assert_not_paused();
+        let account_id = env::signer_account_id();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        assert_ne!(
+            appchain_state.status,
+            AppchainStatus::Voting,
+            "Votes can't be withdrawn while the appchain is in voting."
+        );
+        // The record is removed before the transfer, so it can't be withdrawn twice
+        let account_vote = appchain_state
+            .take_account_vote(&account_id)
+            .expect("You haven't voted to the appchain.");
+        self.set_appchain_state(&appchain_id, &appchain_state);
+
+        ext_token::ft_transfer(
+            account_id.clone(),
+            (account_vote.upvote.0 + account_vote.downvote.0).into(),
+            None,
+            &self.token_contract_id,
+            1,
+            GAS_FOR_FT_TRANSFER_CALL,
+        )
+        .then(ext_self::resolve_withdraw_vote(
+            appchain_id,
+            account_id,
+            account_vote,
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            env::prepaid_gas() / 2,
+        ));
+    }
+
+    pub fn resolve_withdraw_vote(
+        &mut self,
+        appchain_id: AppchainId,
+        account_id: AccountId,
+        account_vote: AccountVote,
+    ) {
+        assert_self();
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                // Nothing is transferred, the vote record is restored.
+                log!(
+                    "Failed to withdraw votes of '{}' to appchain '{}'.",
+                    account_id,
+                    appchain_id
+                );
+                let mut appchain_state = self.get_appchain_state(&appchain_id);
+                appchain_state.restore_account_vote(&account_id, &account_vote);
+                self.set_appchain_state(&appchain_id, &appchain_state);
+            }
+        }
+    }
+
+    /// Withdraw OCT tokens of the caller which are no longer staked or delegated in an appchain
+    pub fn withdraw_unclaimed_balance(&mut self, appchain_id: AppchainId) {
+        self.assert_not_paused();
+        let account_id = env::signer_account_id();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        // The record is removed before the transfer, so it can't be withdrawn twice
+        let amount = appchain_state
+            .take_unclaimed_balance(&account_id)
+            .expect("You have no unclaimed balance in the appchain.");
+        self.set_appchain_state(&appchain_id, &appchain_state);
+
+        ext_token::ft_transfer(
+            account_id.clone(),
+            amount.into(),
+            None,
+            &self.token_contract_id,
+            1,
+            GAS_FOR_FT_TRANSFER_CALL,
+        )
+        .then(ext_self::resolve_withdraw_unclaimed_balance(
+            appchain_id,
+            account_id,
+            amount.into(),
+            &env::current_account_id(),
+            NO_DEPOSIT,
+            env::prepaid_gas() / 2,
+        ));
+    }
+
+    pub fn resolve_withdraw_unclaimed_balance(
+        &mut self,
+        appchain_id: AppchainId,
+        account_id: AccountId,
+        amount: U128,
+    ) {
+        assert_self();
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                // Nothing is transferred, the unclaimed balance is restored.
+                log!(
+                    "Failed to withdraw unclaimed balance of '{}' in appchain '{}', amount is {}.",
+                    account_id,
+                    appchain_id,
+                    amount.0
+                );
+                let mut appchain_state = self.get_appchain_state(&appchain_id);
+                appchain_state.add_unclaimed_balance(&account_id, amount.0);
+                self.set_appchain_state(&appchain_id, &appchain_state);
+            }
+        }
+    }
+
+    /// Update the SubQuery endpoint of an appchain, can be called by the owner or the founder
     pub fn update_subql_url(&mut self, appchain_id: AppchainId, subql_url: String) {
-        self.assert_owner();
         let mut appchain_metadata = self.get_appchain_metadata(&appchain_id);
+        let account_id = env::predecessor_account_id();
+        assert!(
+            account_id.eq(&self.owner) || account_id.eq(&appchain_metadata.founder_id),
+            "Only the owner or the appchain founder can update the subql url."
+        );
         appchain_metadata.update_subql(subql_url);
         self.set_appchain_metadata(&appchain_id, &appchain_metadata);
     }
@@ -726,6 +2092,55 @@ impl OctopusRelay {
         appchain_state.is_message_used(nonce)
     }
 
+    /// Remove records of used messages with nonce below `below_nonce`.
+    ///
+    /// All messages below the watermark must have been settled,
+    /// as they can never be relayed after pruning.
+    pub fn prune_used_messages(&mut self, appchain_id: AppchainId, below_nonce: u64) {
+        self.assert_owner();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.prune_used_messages(below_nonce);
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    /// Get records of relayed unlock messages which were skipped
+    pub fn get_failed_unlocks(
+        &self,
+        appchain_id: AppchainId,
+        start: u64,
+        limit: u64,
+    ) -> Vec<FailedUnlock> {
+        self.get_appchain_state(&appchain_id)
+            .get_failed_unlocks(start, limit)
+    }
+
+    /// Recompute the total locked amount of a token and compare it with the tracked one
+    pub fn audit_locked_tokens(&self, appchain_id: AppchainId, token_id: AccountId) -> LockedAudit {
+        self.get_appchain_state(&appchain_id)
+            .audit_locked_tokens(&token_id)
+    }
+
+    /// Get lock facts of a token which are not settled by unlocks yet
+    pub fn get_unsettled_locks(&self, appchain_id: AppchainId, token_id: AccountId) -> Vec<Locked> {
+        self.get_appchain_state(&appchain_id)
+            .get_unsettled_locks(&token_id)
+    }
+
+    /// Reset the tracked total locked amount of a token to the recomputed one
+    pub fn repair_locked_tokens(&mut self, appchain_id: AppchainId, token_id: AccountId) {
+        self.assert_owner();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        let audit = appchain_state.repair_locked_tokens(&token_id);
+        self.set_appchain_state(&appchain_id, &appchain_state);
+        self.update_bridge_used_val(&appchain_id, &token_id, audit.tracked.0, audit.from_facts.0);
+        log!(
+            "Total locked amount of token '{}' repaired from {} to {}.",
+            token_id,
+            audit.tracked.0,
+            audit.from_facts.0
+        );
+    }
+
     pub fn get_facts(&self, appchain_id: AppchainId, start: SeqNum, limit: SeqNum) -> Vec<Fact> {
         let appchain_state = self.get_appchain_state(&appchain_id);
         let facts = appchain_state.get_facts(&start, &limit);
@@ -739,6 +2154,52 @@ impl OctopusRelay {
         filtered_facts
     }
 
+    /// Get facts of an appchain as they are stored, including the indexes of validator sets
+    pub fn get_raw_facts(
+        &self,
+        appchain_id: AppchainId,
+        start: SeqNum,
+        limit: SeqNum,
+    ) -> Vec<RawFactView> {
+        self.get_appchain_state(&appchain_id)
+            .get_raw_facts(start, limit)
+    }
+
+    /// Get facts of several appchains, in the order of requests
+    ///
+    /// At most `MAX_FACTS_PER_MULTI_VIEW` facts are returned in total, the requests
+    /// after the cap is reached get empty results. Unknown appchains get empty results.
+    pub fn get_facts_multi(&self, requests: Vec<FactsRequest>) -> Vec<Vec<Fact>> {
+        let mut remaining = MAX_FACTS_PER_MULTI_VIEW;
+        requests
+            .into_iter()
+            .map(|request| {
+                if remaining == 0 || !self.appchain_exists(&request.appchain_id) {
+                    return Vec::new();
+                }
+                let facts = self.get_facts(
+                    request.appchain_id,
+                    request.start,
+                    std::cmp::min(request.limit, remaining),
+                );
+                remaining -= facts.len() as u32;
+                facts
+            })
+            .collect()
+    }
+
+    /// Get facts of an appchain created in time range `[from_ts, to_ts)`
+    pub fn get_facts_in_time_range(
+        &self,
+        appchain_id: AppchainId,
+        from_ts: U64,
+        to_ts: U64,
+        limit: SeqNum,
+    ) -> Vec<Fact> {
+        let appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.get_facts_in_time_range(from_ts.into(), to_ts.into(), limit)
+    }
+
     pub fn get_validator_histories(
         &self,
         appchain_id: AppchainId,
@@ -787,4 +2248,402 @@ impl Ownable for OctopusRelay {
  *
  */
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::bridge_token_manager::BridgeTokenManager;
+    use crate::native_token_manager::NativeTokenManager;
+    use crate::pipeline::AppchainPipeline;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, MockedBlockchain};
+
+    fn new_contract(oct_decimals: Option<u32>) -> OctopusRelay {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(accounts(0))
+            .signer_account_id(accounts(1))
+            .build());
+        OctopusRelay::new(
+            accounts(2).into(),
+            2,
+            U128::from(100),
+            3333,
+            U128::from(2000000),
+            oct_decimals,
+        )
+    }
+
+    fn register_appchain(
+        contract: &mut OctopusRelay,
+        appchain_id: &str,
+        bond_tokens: u128,
+        validator_key_type: KeyType,
+    ) {
+        contract.register_appchain(
+            appchain_id.to_string(),
+            "website_url".to_string(),
+            "github_address".to_string(),
+            "github_release".to_string(),
+            "commit_id".to_string(),
+            "email".to_string(),
+            bond_tokens,
+            None,
+            validator_key_type,
+        );
+    }
+
+    /// Create the contract with an appchain `testchain` registered
+    fn setup_contract() -> OctopusRelay {
+        let mut contract = new_contract(None);
+        register_appchain(&mut contract, "testchain", 200, KeyType::Raw32);
+        contract
+    }
+
+    #[test]
+    fn test_get_appchain_with_missing_state() {
+        let mut contract = setup_contract();
+        assert!(contract.get_appchain("testchain".to_string()).is_some());
+
+        contract.appchain_states.remove(&"testchain".to_string());
+        assert!(contract.get_appchain("testchain".to_string()).is_none());
+        assert!(contract.get_appchains(0, 10).is_empty());
+    }
+
+    #[test]
+    fn test_remove_zero_bond_appchain() {
+        let mut contract = new_contract(None);
+        register_appchain(&mut contract, "testchain", 0, KeyType::Raw32);
+        contract.remove_appchain("testchain".to_string());
+
+        // The appchain is removed without a refund promise.
+        assert!(contract.get_appchain("testchain".to_string()).is_none());
+        assert_eq!(contract.get_num_appchains(), 0);
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn test_get_appchain_ids() {
+        let mut contract = new_contract(None);
+        for appchain_id in ["chain0", "chain1", "chain2"].iter() {
+            register_appchain(&mut contract, appchain_id, 0, KeyType::Raw32);
+        }
+
+        assert_eq!(
+            contract.get_appchain_ids(0, 2),
+            vec!["chain0".to_string(), "chain1".to_string()]
+        );
+        assert_eq!(contract.get_appchain_ids(2, 2), vec!["chain2".to_string()]);
+        assert!(contract.get_appchain_ids(4, 2).is_empty());
+        assert_eq!(contract.get_appchain_ids(1, u32::MAX).len(), 2);
+        assert_eq!(contract.get_appchains(1, u32::MAX).len(), 2);
+        assert!(contract
+            .get_appchains_needing_rotation(u32::MAX, u32::MAX)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_bridge_limit_with_oct_decimals() {
+        let get_limit_val = |oct_decimals: u32| -> u128 {
+            let mut contract = new_contract(Some(oct_decimals));
+            assert_eq!(contract.get_oct_decimals(), oct_decimals);
+            register_appchain(&mut contract, "testchain", 0, KeyType::Raw32);
+            let appchain_id = "testchain".to_string();
+            let mut appchain_state = contract.get_appchain_state(&appchain_id);
+            appchain_state.staked_balance = 1000 * 10u128.pow(24);
+            contract.set_appchain_state(&appchain_id, &appchain_state);
+            contract.get_bridge_utilization(appchain_id).limit_val.0
+        };
+
+        // 1000 OCT with 24 decimals, at the price of 2 usd and ratio of 33.33%
+        assert_eq!(get_limit_val(24), 666_600_000);
+        assert_eq!(get_limit_val(18), 666_600_000 * 10u128.pow(6));
+    }
+
+    #[test]
+    fn test_bridge_allowed_amount_with_24_decimals() {
+        let mut contract = setup_contract();
+        let appchain_id = "testchain".to_string();
+        let mut appchain_state = contract.get_appchain_state(&appchain_id);
+        appchain_state.status = AppchainStatus::Booting;
+        appchain_state.staked_balance = 1_000_000_000 * 10u128.pow(18);
+        contract.set_appchain_state(&appchain_id, &appchain_state);
+        let token_id = "wnear.testnet".to_string();
+        contract.register_bridge_token(
+            token_id.clone(),
+            "WNEAR".to_string(),
+            U128::from(1000000),
+            24,
+        );
+        contract.set_bridge_permitted(token_id.clone(), appchain_id.clone(), true);
+
+        // 1e9 OCT at the price of 2 usd and ratio of 33.33%, the product overflows u128
+        assert_eq!(
+            contract.get_bridge_allowed_amount(appchain_id, token_id),
+            U128::from(666_600_000 * 10u128.pow(24))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The decimals of bridge token should be at most 24.")]
+    fn test_register_bridge_token_with_too_many_decimals() {
+        let mut contract = new_contract(None);
+        contract.register_bridge_token(
+            "token.testnet".to_string(),
+            "TKN".to_string(),
+            U128::from(1000000),
+            25,
+        );
+    }
+
+    #[test]
+    fn test_incremental_bridge_used_val() {
+        let mut contract = setup_contract();
+        let appchain_id = "testchain".to_string();
+        let mut appchain_state = contract.get_appchain_state(&appchain_id);
+        appchain_state.status = AppchainStatus::Booting;
+        appchain_state.staked_balance = 1000000 * 10u128.pow(18);
+        contract.set_appchain_state(&appchain_id, &appchain_state);
+        for (token_id, decimals) in [("usdc.testnet", 6), ("weth.testnet", 18)].iter() {
+            contract.register_bridge_token(
+                token_id.to_string(),
+                token_id.to_string(),
+                U128::from(1000000),
+                *decimals,
+            );
+            contract.set_bridge_permitted(token_id.to_string(), appchain_id.clone(), true);
+        }
+        let assert_used_val_consistent = |contract: &OctopusRelay| {
+            let appchain_state = contract.get_appchain_state(&appchain_id);
+            assert_eq!(
+                contract
+                    .get_bridge_utilization(appchain_id.clone())
+                    .used_val,
+                U128::from(contract.compute_bridge_used_val(&appchain_state))
+            );
+        };
+
+        contract.lock_token(
+            appchain_id.clone(),
+            "receiver".to_string(),
+            "alice".to_string(),
+            "usdc.testnet".to_string(),
+            300_123_456,
+        );
+        contract.lock_token(
+            appchain_id.clone(),
+            "receiver".to_string(),
+            "alice".to_string(),
+            "weth.testnet".to_string(),
+            2 * 10u128.pow(18) + 1,
+        );
+        assert_used_val_consistent(&contract);
+        assert!(
+            contract
+                .get_bridge_utilization(appchain_id.clone())
+                .used_val
+                .0
+                > 0
+        );
+
+        contract.set_bridge_token_price("weth.testnet".to_string(), U128::from(3000000000), None);
+        assert_used_val_consistent(&contract);
+
+        contract.lock_token(
+            appchain_id.clone(),
+            "receiver".to_string(),
+            "bob".to_string(),
+            "usdc.testnet".to_string(),
+            100_000_001,
+        );
+        assert_used_val_consistent(&contract);
+    }
+
+    #[test]
+    fn test_set_validator_set_cycle_for_new_appchains() {
+        let mut contract = setup_contract();
+        contract.set_validator_set_cycle(U128::from(60_000_000_000));
+        register_appchain(&mut contract, "newchain", 0, KeyType::Raw32);
+        assert_eq!(
+            contract
+                .get_appchain_state(&"testchain".to_string())
+                .validator_set_cycle,
+            DEFAULT_VALIDATOR_SET_CYCLE
+        );
+        assert_eq!(
+            contract
+                .get_appchain_state(&"newchain".to_string())
+                .validator_set_cycle,
+            60_000_000_000
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The contract is paused.")]
+    fn test_unstake_when_paused() {
+        let mut contract = new_contract(None);
+        contract.paused = true;
+        contract.unstake("testchain".to_string());
+    }
+
+    #[test]
+    fn test_bridge_token_value_of_large_amount() {
+        testing_env!(VMContextBuilder::new().build());
+        let bridge_token = RelayedBridgeToken::new(
+            "wnear.testnet".to_string(),
+            "WNEAR".to_string(),
+            relayed_bridge_token::BridgingStatus::Activated,
+            U128::from(10u128.pow(12)),
+            24,
+        );
+        // 1e6 tokens of 24 decimals at the price of 1e6 usd, the product overflows u128
+        assert_eq!(bridge_token.value_of(10u128.pow(30)), 10u128.pow(18));
+        assert_eq!(bridge_token.value_of(u128::MAX), u128::MAX / 10u128.pow(12));
+    }
+
+    #[test]
+    fn test_staking_balance_delta() {
+        let mut contract = setup_contract();
+        assert_eq!(contract.get_staking_balance_delta().delta, I128::from(0));
+
+        // Force an inconsistency between the appchain and the relay.
+        let appchain_id = "testchain".to_string();
+        let mut appchain_state = contract.get_appchain_state(&appchain_id);
+        appchain_state.staked_balance = 300;
+        contract.set_appchain_state(&appchain_id, &appchain_state);
+        contract.total_staked_balance = 200;
+
+        let staking_delta = contract.get_staking_balance_delta();
+        assert_eq!(staking_delta.relay_total, U128::from(200));
+        assert_eq!(staking_delta.appchains_sum, U128::from(300));
+        assert_eq!(staking_delta.delta, I128::from(-100));
+    }
+
+    #[test]
+    fn test_admin_activate_appchain() {
+        let mut contract = setup_contract();
+        let appchain_id = "testchain".to_string();
+        contract.pass_appchain(appchain_id.clone());
+        contract.appchain_go_staging(appchain_id.clone());
+        let mut appchain_state = contract.get_appchain_state(&appchain_id);
+        appchain_state.seed_validator(&"val0".to_string(), &"alice".to_string(), &100);
+        appchain_state.seed_validator(&"val1".to_string(), &"bob".to_string(), &100);
+        contract.set_appchain_state(&appchain_id, &appchain_state);
+
+        let status = contract.admin_activate_appchain(
+            appchain_id.clone(),
+            "boot_nodes".to_string(),
+            "rpc_endpoint".to_string(),
+            "chain_spec_url".to_string(),
+            "chain_spec_hash".to_string(),
+            "chain_spec_raw_url".to_string(),
+            "chain_spec_raw_hash".to_string(),
+        );
+
+        // The appchain is booted without a refund promise of the bond.
+        assert_eq!(status, Some(AppchainStatus::Booting));
+        assert!(get_created_receipts().is_empty());
+    }
+
+    fn register_staging_testchain(validator_key_type: KeyType) -> OctopusRelay {
+        let mut contract = new_contract(None);
+        register_appchain(&mut contract, "testchain", 0, validator_key_type);
+        contract.pass_appchain("testchain".to_string());
+        contract.appchain_go_staging("testchain".to_string());
+        contract
+    }
+
+    #[test]
+    fn test_stake_with_key_types() {
+        let validator_id =
+            "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d".to_string();
+        for key_type in [KeyType::Raw32, KeyType::Sr25519, KeyType::Ed25519].iter() {
+            let mut contract = register_staging_testchain(key_type.clone());
+            contract.stake("testchain".to_string(), validator_id.clone(), 100);
+            let validator = contract
+                .get_validator("testchain".to_string(), validator_id.clone())
+                .unwrap();
+            assert_eq!(&validator.key_type, key_type);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "address should be 32 bytes long")]
+    fn test_stake_with_wrong_length_key() {
+        let mut contract = register_staging_testchain(KeyType::Sr25519);
+        contract.stake(
+            "testchain".to_string(),
+            "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da2".to_string(),
+            100,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The validator id is not a valid Ed25519 public key.")]
+    fn test_stake_with_zero_key() {
+        let mut contract = register_staging_testchain(KeyType::Ed25519);
+        contract.stake(
+            "testchain".to_string(),
+            format!("0x{}", "0".repeat(64)),
+            100,
+        );
+    }
+
+    fn register_testchain_with_native_token() -> OctopusRelay {
+        let mut contract = setup_contract();
+        contract.register_native_token("testchain".to_string(), "wrong_token".to_string());
+        contract
+    }
+
+    #[test]
+    fn test_update_native_token_before_booting() {
+        let mut contract = register_testchain_with_native_token();
+        contract.update_native_token("testchain".to_string(), "native_token".to_string());
+        assert_eq!(
+            contract.get_native_token("testchain".to_string()),
+            Some("native_token".to_string())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The native token can not be changed after it has been minted.")]
+    fn test_update_native_token_after_minting() {
+        let mut contract = register_testchain_with_native_token();
+        let appchain_id = "testchain".to_string();
+        let mut appchain_state = contract.get_appchain_state(&appchain_id);
+        appchain_state.status = AppchainStatus::Booting;
+        appchain_state.record_appchain_burn(100);
+        appchain_state.record_native_mint(100);
+        appchain_state.reserve_native_mint(100);
+        contract.set_appchain_state(&appchain_id, &appchain_state);
+        contract.update_native_token(appchain_id, "native_token".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "The native token can not be changed after it has been minted.")]
+    fn test_update_native_token_after_burning_minted() {
+        let mut contract = register_testchain_with_native_token();
+        let appchain_id = "testchain".to_string();
+        let mut appchain_state = contract.get_appchain_state(&appchain_id);
+        appchain_state.status = AppchainStatus::Frozen;
+        appchain_state.record_appchain_burn(100);
+        appchain_state.record_native_mint(100);
+        appchain_state.reserve_native_mint(100);
+        appchain_state.release_native_mint(100);
+        contract.set_appchain_state(&appchain_id, &appchain_state);
+        contract.update_native_token(appchain_id, "native_token".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed mint not found.")]
+    fn test_retry_mint_twice() {
+        let mut contract = register_testchain_with_native_token();
+        let appchain_id = "testchain".to_string();
+        let mut appchain_state = contract.get_appchain_state(&appchain_id);
+        appchain_state.record_failed_mint(1, "bob".to_string(), U128::from(100));
+        contract.set_appchain_state(&appchain_id, &appchain_state);
+
+        contract.retry_mint(appchain_id.clone(), 1);
+        assert!(contract.get_failed_mints(appchain_id.clone()).is_empty());
+        // The first retry is not resolved yet.
+        contract.retry_mint(appchain_id, 1);
+    }
+}
@@ -5,6 +5,13 @@ pub trait NativeTokenManager {
     /// Register a new bridge token
     fn register_native_token(&mut self, appchain_id: AppchainId, token_id: AccountId);
     fn get_native_token(&self, appchain_id: AppchainId) -> Option<AccountId>;
+    /// Replace the registered native token, only if no native token has ever been minted on NEAR
+    fn update_native_token(&mut self, appchain_id: AppchainId, token_id: AccountId);
+    /// Set the maximum net minted supply of the native token on NEAR, 0 means unlimited
+    fn set_native_mint_cap(&mut self, appchain_id: AppchainId, token_id: AccountId, cap: U128);
+    fn get_native_mint_cap(&self, appchain_id: AppchainId) -> U128;
+    /// Get the accumulated amounts of native token burnt on the appchain and minted on NEAR
+    fn get_native_token_accounting(&self, appchain_id: AppchainId) -> (U128, U128);
 }
 
 #[near_bindgen]
@@ -22,4 +29,43 @@ impl NativeTokenManager for OctopusRelay {
     fn get_native_token(&self, appchain_id: AppchainId) -> Option<AccountId> {
         self.appchain_native_tokens.get(&appchain_id)
     }
+
+    fn update_native_token(&mut self, appchain_id: AppchainId, token_id: AccountId) {
+        self.assert_owner();
+        assert!(
+            self.appchain_native_tokens.get(&appchain_id).is_some(),
+            "The native token of this appchain is not registered."
+        );
+        // Any mint on NEAR, even if burnt back later, binds the native token of the appchain
+        assert_eq!(
+            self.get_appchain_state(&appchain_id).minted_on_near_total,
+            0,
+            "The native token can not be changed after it has been minted."
+        );
+        self.appchain_native_tokens.insert(&appchain_id, &token_id);
+    }
+
+    fn set_native_mint_cap(&mut self, appchain_id: AppchainId, token_id: AccountId, cap: U128) {
+        self.assert_owner();
+        assert_eq!(
+            self.get_native_token(appchain_id.clone()),
+            Some(token_id),
+            "The token is not the native token of this appchain."
+        );
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        appchain_state.native_mint_cap = cap.0;
+        self.set_appchain_state(&appchain_id, &appchain_state);
+    }
+
+    fn get_native_mint_cap(&self, appchain_id: AppchainId) -> U128 {
+        self.get_appchain_state(&appchain_id).native_mint_cap.into()
+    }
+
+    fn get_native_token_accounting(&self, appchain_id: AppchainId) -> (U128, U128) {
+        let appchain_state = self.get_appchain_state(&appchain_id);
+        (
+            appchain_state.burned_on_appchain_total.into(),
+            appchain_state.minted_on_near_total.into(),
+        )
+    }
 }
@@ -40,8 +40,25 @@ pub trait AppchainPipeline {
         chain_spec_raw_url: String,
         chain_spec_raw_hash: String,
     ) -> Option<AppchainStatus>;
-    /// Freeze an appchain
+    /// Activate an appchain without refunding the bond of it,
+    /// for recovery when the refund of `activate_appchain` was done or should be skipped.
+    /// Can only be called by the owner of Octopus relay.
+    fn admin_activate_appchain(
+        &mut self,
+        appchain_id: AppchainId,
+        boot_nodes: String,
+        rpc_endpoint: String,
+        chain_spec_url: String,
+        chain_spec_hash: String,
+        chain_spec_raw_url: String,
+        chain_spec_raw_hash: String,
+    ) -> Option<AppchainStatus>;
+    /// Freeze a booting appchain (change its status to `AppchainStatus::Frozen`).
+    /// Can only be called by the owner of Octopus relay.
     fn freeze_appchain(&mut self, appchain_id: AppchainId);
+    /// Unfreeze a frozen appchain (change its status back to `AppchainStatus::Booting`).
+    /// Can only be called by the owner of Octopus relay.
+    fn unfreeze_appchain(&mut self, appchain_id: AppchainId);
 }
 
 #[near_bindgen]
@@ -60,20 +77,24 @@ impl AppchainPipeline for OctopusRelay {
         let bond_tokens = appchain_metadata.bond_tokens;
         let account_id = appchain_metadata.founder_id;
 
-        ext_token::ft_transfer(
-            account_id,
-            (bond_tokens / 10).into(),
-            None,
-            &self.token_contract_id,
-            1,
-            GAS_FOR_FT_TRANSFER_CALL,
-        )
-        .then(ext_self::resolve_remove_appchain(
-            appchain_id.clone(),
-            &env::current_account_id(),
-            NO_DEPOSIT,
-            env::prepaid_gas() / 2,
-        ));
+        if bond_tokens > 0 {
+            ext_token::ft_transfer(
+                account_id,
+                (bond_tokens / 10).into(),
+                None,
+                &self.token_contract_id,
+                1,
+                GAS_FOR_FT_TRANSFER_CALL,
+            )
+            .then(ext_self::resolve_remove_appchain(
+                appchain_id.clone(),
+                &env::current_account_id(),
+                NO_DEPOSIT,
+                env::prepaid_gas() / 2,
+            ));
+        } else {
+            self.internal_remove_appchain(appchain_id);
+        }
     }
     //
     fn resolve_remove_appchain(&mut self, appchain_id: AppchainId) {
@@ -81,12 +102,7 @@ impl AppchainPipeline for OctopusRelay {
         // Update state
         match env::promise_result(0) {
             PromiseResult::NotReady => unreachable!(),
-            PromiseResult::Successful(_) => {
-                self.appchain_metadatas.remove(&appchain_id);
-                self.get_appchain_state(&appchain_id).clear_extra_storage();
-                self.appchain_states.remove(&appchain_id);
-                self.remove_appchain_id(appchain_id.clone());
-            }
+            PromiseResult::Successful(_) => self.internal_remove_appchain(appchain_id),
             PromiseResult::Failed => {}
         }
     }
@@ -133,10 +149,16 @@ impl AppchainPipeline for OctopusRelay {
             AppchainStatus::Staging,
             "Appchain is not in staging."
         );
+        let earliest_activation_time =
+            appchain_state.staging_started_at + self.min_staging_duration_nanos;
+        assert!(
+            env::block_timestamp() >= earliest_activation_time,
+            "Appchain can not be activated before {}.",
+            earliest_activation_time
+        );
         // Check validators
         assert!(
-            appchain_state.validators.len().try_into().unwrap_or(0)
-                >= self.appchain_minimum_validators,
+            appchain_state.validators.len() >= u64::from(self.appchain_minimum_validators),
             "Insufficient number of appchain validators"
         );
 
@@ -204,6 +226,42 @@ impl AppchainPipeline for OctopusRelay {
         }
     }
     //
+    fn admin_activate_appchain(
+        &mut self,
+        appchain_id: AppchainId,
+        boot_nodes: String,
+        rpc_endpoint: String,
+        chain_spec_url: String,
+        chain_spec_hash: String,
+        chain_spec_raw_url: String,
+        chain_spec_raw_hash: String,
+    ) -> Option<AppchainStatus> {
+        self.assert_owner();
+        let appchain_state = self.get_appchain_state(&appchain_id);
+        assert_eq!(
+            appchain_state.status,
+            AppchainStatus::Staging,
+            "Appchain is not in staging."
+        );
+        assert!(
+            appchain_state.validators.len() >= u64::from(self.appchain_minimum_validators),
+            "Insufficient number of appchain validators"
+        );
+        log!(
+            "Appchain '{}' is activated without bond refund.",
+            appchain_id
+        );
+        self.internal_activate_appchain(
+            appchain_id,
+            boot_nodes,
+            rpc_endpoint,
+            chain_spec_url,
+            chain_spec_hash,
+            chain_spec_raw_url,
+            chain_spec_raw_hash,
+        )
+    }
+    //
     fn freeze_appchain(&mut self, appchain_id: AppchainId) {
         self.assert_owner();
         let mut appchain_state = self.get_appchain_state(&appchain_id);
@@ -216,11 +274,43 @@ impl AppchainPipeline for OctopusRelay {
 
         // Update state
         appchain_state.freeze();
-        self.set_appchain_state(&appchain_id, &appchain_state)
+        self.set_appchain_state(&appchain_id, &appchain_state);
+        log!(
+            "Appchain '{}' is frozen at {}.",
+            appchain_id,
+            appchain_state.frozen_at
+        );
+    }
+    //
+    fn unfreeze_appchain(&mut self, appchain_id: AppchainId) {
+        self.assert_owner();
+        let mut appchain_state = self.get_appchain_state(&appchain_id);
+        assert_eq!(
+            appchain_state.status,
+            AppchainStatus::Frozen,
+            "Appchain is not frozen."
+        );
+        appchain_state.unfreeze();
+        self.set_appchain_state(&appchain_id, &appchain_state);
+        log!("Appchain '{}' is unfrozen.", appchain_id);
     }
 }
 
 impl OctopusRelay {
+    //
+    fn internal_remove_appchain(&mut self, appchain_id: AppchainId) {
+        self.appchain_metadatas.remove(&appchain_id);
+        self.get_appchain_state(&appchain_id).clear_extra_storage();
+        self.appchain_states.remove(&appchain_id);
+        self.bridge_used_values.remove(&appchain_id);
+        // Not `remove_appchain_id`, which can only be called by the contract itself
+        let index = self
+            .appchain_id_list
+            .iter()
+            .position(|id| id == appchain_id)
+            .unwrap() as u64;
+        self.appchain_id_list.swap_remove(index);
+    }
     //
     fn internal_activate_appchain(
         &mut self,
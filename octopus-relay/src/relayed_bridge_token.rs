@@ -1,8 +1,9 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::UnorderedMap;
 use near_sdk::json_types::U128;
-use near_sdk::AccountId;
+use near_sdk::{env, AccountId, Balance, Timestamp};
 
+use crate::bridge_token_manager::mul_div_saturating;
 use crate::storage_key::StorageKey;
 use crate::types::{BridgeStatus, BridgeToken};
 use crate::AppchainId;
@@ -30,6 +31,9 @@ pub struct RelayedBridgeToken {
     price: U128,
     decimals: u32,
     appchain_permitted: UnorderedMap<AppchainId, bool>,
+    price_updated_at: Timestamp,
+    resume_at: Option<Timestamp>,
+    limit_ratio_override: Option<u16>,
 }
 
 impl RelayedBridgeToken {
@@ -50,6 +54,24 @@ impl RelayedBridgeToken {
             appchain_permitted: UnorderedMap::new(
                 StorageKey::RelayedBridgeTokenPermissions { token_id }.into_bytes(),
             ),
+            price_updated_at: 0,
+            resume_at: None,
+            limit_ratio_override: None,
+        }
+    }
+    /// Create an instance with the existing bridging permissions of appchains,
+    /// used by the storage migration
+    pub fn with_permissions(
+        token_id: AccountId,
+        symbol: String,
+        bridging_status: BridgingStatus,
+        price: U128,
+        decimals: u32,
+        appchain_permitted: UnorderedMap<AppchainId, bool>,
+    ) -> Self {
+        RelayedBridgeToken {
+            appchain_permitted,
+            ..RelayedBridgeToken::new(token_id, symbol, bridging_status, price, decimals)
         }
     }
     /// Get id of the bridge token
@@ -64,13 +86,37 @@ impl RelayedBridgeToken {
     pub fn price(&self) -> U128 {
         self.price.clone()
     }
+    /// Get the value of an amount of the bridge token, at the current price,
+    /// saturating at `u128::MAX`
+    pub fn value_of(&self, amount: Balance) -> Balance {
+        mul_div_saturating(amount, self.price.0, (10 as u128).pow(self.decimals))
+    }
+    /// Get the last update time of price of the bridge token
+    pub fn price_updated_at(&self) -> Timestamp {
+        self.price_updated_at
+    }
     /// Get symbol of the bridge token
     pub fn symbol(&self) -> String {
         self.symbol.clone()
     }
     /// Get status of the bridge token
+    ///
+    /// A paused token with a resuming time is treated as activated once the time is reached.
     pub fn bridging_status(&self) -> BridgingStatus {
-        self.bridging_status.clone()
+        match (&self.bridging_status, self.resume_at) {
+            (BridgingStatus::Paused, Some(resume_at)) if env::block_timestamp() >= resume_at => {
+                BridgingStatus::Activated
+            }
+            (status, _) => status.clone(),
+        }
+    }
+    /// Get the time when the paused bridge token is resumed automatically
+    pub fn resume_at(&self) -> Option<Timestamp> {
+        self.resume_at
+    }
+    /// Get the bridge limit ratio of the token, `None` means the global ratio is used
+    pub fn limit_ratio_override(&self) -> Option<u16> {
+        self.limit_ratio_override
     }
     /// Get permitted flag of an appchain
     pub fn is_permitted_of(&self, appchain_id: &AppchainId) -> bool {
@@ -78,7 +124,7 @@ impl RelayedBridgeToken {
     }
     /// Convert to struct `BridgeToken`
     pub fn to_bridge_token(&self) -> BridgeToken {
-        let status = match self.bridging_status {
+        let status = match self.bridging_status() {
             BridgingStatus::Activated => BridgeStatus::Active,
             BridgingStatus::Paused => BridgeStatus::Paused,
             BridgingStatus::Closed => BridgeStatus::Closed,
@@ -94,21 +140,33 @@ impl RelayedBridgeToken {
     /// Set price of the bridge token
     pub fn set_price(&mut self, price: &U128) {
         self.price = price.clone();
+        self.price_updated_at = env::block_timestamp();
     }
     /// Activate the bridging of the token
     pub fn activate_bridging(&mut self) {
         self.bridging_status = BridgingStatus::Activated;
+        self.resume_at = None;
     }
-    /// Pause the bridging of the token
-    pub fn pause_bridging(&mut self) {
+    /// Pause the bridging of the token, `resume_at` is the time to resume it automatically
+    pub fn pause_bridging(&mut self, resume_at: Option<Timestamp>) {
         self.bridging_status = BridgingStatus::Paused;
+        self.resume_at = resume_at;
     }
     /// Close the bridging of the token
     pub fn close_bridging(&mut self) {
         self.bridging_status = BridgingStatus::Closed;
+        self.resume_at = None;
+    }
+    /// Set the bridge limit ratio of the token, `None` to use the global ratio
+    pub fn set_limit_ratio_override(&mut self, ratio: Option<u16>) {
+        self.limit_ratio_override = ratio;
     }
     /// Set bridging permission for appchain
     pub fn set_bridging_permission(&mut self, appchain_id: &AppchainId, permitted: &bool) {
         self.appchain_permitted.insert(appchain_id, &permitted);
     }
+    /// Clear bridging permissions for all appchains
+    pub fn clear_bridging_permissions(&mut self) {
+        self.appchain_permitted.clear();
+    }
 }
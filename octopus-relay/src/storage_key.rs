@@ -17,6 +17,7 @@ pub enum StorageKey {
     ValidatorIndexToId(AppchainId),
     ValidatorIdToIndex(AppchainId),
     ValidatorIndexes(AppchainId),
+    ValidatorIndexLists(AppchainId),
     DelegatorHistoryLists(AppchainId, ValidatorId),
     DelegatorIndexToId(AppchainId, ValidatorId),
     DelegatorIdToIndex(AppchainId, ValidatorId),
@@ -48,7 +49,13 @@ pub enum StorageKey {
         history_index: HistoryIndex,
     },
     AppchainTotalLockedTokens(AppchainId),
+    AppchainTotalUnlockedTokens(AppchainId),
     UsedMessage(AppchainId),
+    FailedUnlocks(AppchainId),
+    FailedMints(AppchainId),
+    FailedRemovals(AppchainId),
+    AccountVotes(AppchainId),
+    UnclaimedBalances(AppchainId),
     AppchainValidator(AppchainId, ValidatorId),
     AppchainDelegators(AppchainId, ValidatorId),
     AppchainDelegator(AppchainId, ValidatorId, DelegatorId),
@@ -60,6 +67,8 @@ pub enum StorageKey {
         token_id: AccountId,
     },
     AppchainNativeTokens,
+    AppchainCategories,
+    BridgeUsedValues,
 }
 
 impl StorageKey {
@@ -78,6 +87,7 @@ impl StorageKey {
             StorageKey::ValidatorIndexToId(appchain_id) => format!("{}%vi", appchain_id),
             StorageKey::ValidatorIdToIndex(appchain_id) => format!("{}%iv", appchain_id),
             StorageKey::ValidatorIndexes(appchain_id) => format!("{}%vis", appchain_id),
+            StorageKey::ValidatorIndexLists(appchain_id) => format!("{}%vil", appchain_id),
             StorageKey::DelegatorHistoryLists(appchain_id, validator_id) => {
                 format!("{}{}%dhs", appchain_id, validator_id)
             }
@@ -135,7 +145,13 @@ impl StorageKey {
                 )
             }
             StorageKey::AppchainTotalLockedTokens(appchain_id) => format!("{}t", appchain_id),
+            StorageKey::AppchainTotalUnlockedTokens(appchain_id) => format!("{}%ut", appchain_id),
             StorageKey::UsedMessage(appchain_id) => format!("{}%um", appchain_id),
+            StorageKey::FailedUnlocks(appchain_id) => format!("{}%fus", appchain_id),
+            StorageKey::FailedMints(appchain_id) => format!("{}%fms", appchain_id),
+            StorageKey::FailedRemovals(appchain_id) => format!("{}%frs", appchain_id),
+            StorageKey::AccountVotes(appchain_id) => format!("{}%avs", appchain_id),
+            StorageKey::UnclaimedBalances(appchain_id) => format!("{}%ucb", appchain_id),
             StorageKey::AppchainValidator(appchain_id, validator_id) => {
                 format!("{}{}", appchain_id, validator_id)
             }
@@ -153,6 +169,8 @@ impl StorageKey {
                 format!("rt{}ps", token_id)
             }
             StorageKey::AppchainNativeTokens => "ant".to_string(),
+            StorageKey::AppchainCategories => "acs".to_string(),
+            StorageKey::BridgeUsedValues => "buv".to_string(),
         }
     }
     pub fn into_bytes(&self) -> Vec<u8> {
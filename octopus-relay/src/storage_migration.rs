@@ -1,57 +1,186 @@
-//! Sample implementation of storage migration for adding a field
-//! to an internal struct of relay contract
+//! One-time storage migration for adding fields `category` and `operational_note`
+//! to struct `AppchainMetadata`,
+//! fields `seed_deposit_balance`, `oct_token_price_updated_at`, `price_update_cooldown`,
+//! `max_messages_per_relay`, `registration_open`, `min_staging_duration_nanos`,
+//! `staking_globally_paused`, `appchain_categories`, `oct_decimals`, `bridge_used_values`,
+//! `max_receiver_len`, `validator_set_cycle`, `paused` and `minimum_delegation_amount`
+//! to struct `OctopusRelay`,
+//! field `self_stake` to struct `ValidatorHistory`,
+//! fields `timestamp` and `staked_balance` to struct `ValidatorHistoryIndexSet`,
+//! whose field `indexes` is replaced by `indexes_set_id` and `validators_len`,
+//! field `timestamp` to structs `Locked` and `Burned`
+//! fields `delegated_total`, `enabled`, `refund_account` and `key_type`
+//! to struct `AppchainValidator`,
+//! fields `price_updated_at`, `resume_at` and `limit_ratio_override`
+//! to struct `RelayedBridgeToken`
+//! and all fields added after `validator_indexes` to struct `AppchainState`,
+//! such as `staging_started_at`, `account_votes`, `mmr_root`, `failed_mints`,
+//! `max_validators`, `validator_key_type`, `validator_set_cycle`, `event_seq`,
+//! `unclaimed_balances`, `validator_index_lists` and `latest_indexes_set_id`
 //!
 //! Every time we change the fields of a struct in relay contract,
 //! we need to write an one-time migration function for relay contract like this module.
 //!
-//! The following implementation shows how to migrate storage of OctopusRelay contract
-//! when we need to add a field `note` to struct `AppchainValidator`.
-use crate::appchain::state::{AppchainDelegator, AppchainValidator};
+//! The existing appchains will not be tagged with any category or note,
+//! and the allowlist of categories will be empty.
+//!
+//! The decimals of OCT token will be 18, which was hardcoded before.
+//! No OCT token is deposited for seeding validators, the price of OCT token is treated as
+//! never updated and there is no cooldown between price updates.
+//!
+//! Receivers of locked or burnt tokens will be limited to 128 bytes.
+//!
+//! The cycle of validator sets will be 20 minutes, which was hardcoded before.
+//!
+//! The minimum delegation amount will be a tenth of the minimum staking amount.
+//!
+//! The new fields of existing appchain states will be the same as a newly registered appchain,
+//! which means no limits, no recorded failures, all bridging directions enabled,
+//! facts of assets stored and raw 32 bytes keys of validators.
+//! Events of existing appchains will be numbered from `event_seq` 0.
+//! No balance of existing appchains is unclaimed.
+//! The votes before the migration were not recorded per account, so they can't be withdrawn.
+//!
+//! The prices of existing bridge tokens are treated as never updated, paused tokens
+//! won't be resumed automatically and the global bridge limit ratio applies to them.
+//!
+//! The total value of bridge tokens locked in each appchain is computed by current locks.
+//!
+//! The unlocked amount of each token locked in existing appchains wasn't accumulated,
+//! so it will be the amount of its lock facts minus its total locked amount.
+//!
+//! The `weight` of existing validator histories didn't count delegated balance,
+//! so their `self_stake` will be set to `weight`.
+//!
+//! The staked balance of existing validator sets is unknown, so it will be `None`.
+//! Their indexes of validators are moved to `validator_index_lists` of the appchain,
+//! and shared by the following validator sets until they are changed.
+//!
+//! The creation time of existing facts is unknown, so their `timestamp` will be 0.
+//!
+//! The delegated total of existing validators is summed up from their delegators.
+//!
+//! The existing validators will be enabled, and their stake will be refunded
+//! to their own accounts. Their ids are treated as raw 32 bytes keys.
+//!
+//! Staking of all appchains will be paused after the migration,
+//! until the owner resumes it by `resume_staking_after_migration`.
+use crate::appchain::delegator::{AppchainDelegator, DelegatorHistoryList};
+use crate::appchain::fact::RawFact;
+use crate::appchain::validator::{
+    AppchainValidator, ValidatorHistory, ValidatorHistoryIndexSet, ValidatorHistoryList,
+};
+use crate::appchain_prover::AppchainProver;
+use crate::relayed_bridge_token::BridgingStatus;
+use crate::types::{
+    Burned, DelegatorId, DelegatorIndex, HistoryIndex, KeyType, Locked, SeqNum, SetId,
+    ValidatorIndex,
+};
 use crate::*;
+use near_sdk::collections::LookupMap;
+
+/// Metadata of an appchain of Octopus Network
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldAppchainMetadata {
+    pub id: AppchainId,
+    pub founder_id: AccountId,
+    pub website_url: String,
+    pub github_address: String,
+    pub github_release: String,
+    pub commit_id: String,
+    pub email: String,
+    pub chain_spec_url: String,
+    pub chain_spec_hash: String,
+    pub chain_spec_raw_url: String,
+    pub chain_spec_raw_hash: String,
+    pub boot_nodes: String,
+    pub rpc_endpoint: String,
+    pub bond_tokens: Balance,
+    pub block_height: BlockHeight,
+    pub subql_url: String,
+}
+
+impl OldAppchainMetadata {
+    pub fn migrate_state(appchain_id: &AppchainId) {
+        let storage_key = StorageKey::AppchainMetadata(appchain_id.clone()).into_bytes();
+        if let Some(data) = env::storage_read(&storage_key) {
+            if let Ok(metadata) = OldAppchainMetadata::try_from_slice(&data) {
+                env::log(format!("Migrating metadata of appchain '{}'", &metadata.id).as_bytes());
+                let new_state = AppchainMetadata {
+                    id: metadata.id.clone(),
+                    founder_id: metadata.founder_id,
+                    website_url: metadata.website_url,
+                    github_address: metadata.github_address,
+                    github_release: metadata.github_release,
+                    commit_id: metadata.commit_id,
+                    email: metadata.email,
+                    chain_spec_url: metadata.chain_spec_url,
+                    chain_spec_hash: metadata.chain_spec_hash,
+                    chain_spec_raw_url: metadata.chain_spec_raw_url,
+                    chain_spec_raw_hash: metadata.chain_spec_raw_hash,
+                    boot_nodes: metadata.boot_nodes,
+                    rpc_endpoint: metadata.rpc_endpoint,
+                    bond_tokens: metadata.bond_tokens,
+                    block_height: metadata.block_height,
+                    subql_url: metadata.subql_url,
+                    category: None,
+                    operational_note: None,
+                };
+                if let Ok(new_data) = new_state.try_to_vec() {
+                    assert!(
+                        env::storage_write(&storage_key, &new_data),
+                        "Migration for metadata of appchain '{}' failed",
+                        &metadata.id
+                    );
+                }
+            }
+        }
+    }
+}
 
 /// Appchain validator of an appchain
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct OldAppchainValidator {
-    /// Id of appchain validator
     pub validator_id: ValidatorId,
-    /// Account id of the validator
     pub account_id: AccountId,
-    /// Staked balance of the validator
     pub amount: Balance,
-    /// Block height which the validator started staking
     pub block_height: BlockHeight,
-    /// Delegators of the validator
     pub delegators: UnorderedMap<DelegatorId, LazyOption<AppchainDelegator>>,
+    pub delegator_history_lists: LookupMap<DelegatorIndex, LazyOption<DelegatorHistoryList>>,
+    pub delegator_index_to_id: LookupMap<DelegatorIndex, DelegatorId>,
+    pub delegator_last_index: DelegatorIndex,
+    pub delegator_id_to_index: LookupMap<DelegatorId, DelegatorIndex>,
+    pub delegator_indexes: UnorderedMap<DelegatorIndex, bool>,
 }
 
 impl OldAppchainValidator {
-    pub fn migrate_state(
-        appchain_id: &AppchainId,
-        validator_id: &ValidatorId,
-        new_note_of_validator: &String,
-    ) {
+    pub fn migrate_state(appchain_id: &AppchainId, validator_id: &ValidatorId) {
         let storage_key =
             StorageKey::AppchainValidator(appchain_id.clone(), validator_id.clone()).into_bytes();
         if let Some(data) = env::storage_read(&storage_key) {
             if let Ok(validator) = OldAppchainValidator::try_from_slice(&data) {
-                env::log(
-                    format!("Migrating state of validator '{}'", &validator.account_id).as_bytes(),
-                );
-                let mut delegators: UnorderedMap<DelegatorId, LazyOption<AppchainDelegator>> =
-                    UnorderedMap::new(
-                        StorageKey::AppchainDelegators(appchain_id.clone(), validator_id.clone())
-                            .into_bytes(),
-                    );
-                validator.delegators.iter().for_each(|(k, v)| {
-                    delegators.insert(&k, &v);
-                });
+                let delegated_total = validator
+                    .delegators
+                    .values_as_vector()
+                    .iter()
+                    .filter_map(|d| d.get())
+                    .map(|d| d.amount)
+                    .sum();
                 let new_state = AppchainValidator {
-                    validator_id: validator.validator_id.clone(),
+                    validator_id: validator.validator_id,
                     account_id: validator.account_id.clone(),
                     amount: validator.amount,
                     block_height: validator.block_height,
-                    delegators,
-                    note: new_note_of_validator.clone(),
+                    delegators: validator.delegators,
+                    delegator_history_lists: validator.delegator_history_lists,
+                    delegator_index_to_id: validator.delegator_index_to_id,
+                    delegator_last_index: validator.delegator_last_index,
+                    delegator_id_to_index: validator.delegator_id_to_index,
+                    delegator_indexes: validator.delegator_indexes,
+                    delegated_total,
+                    enabled: true,
+                    refund_account: None,
+                    key_type: KeyType::Raw32,
                 };
                 if let Ok(new_data) = new_state.try_to_vec() {
                     assert!(
@@ -65,23 +194,328 @@ impl OldAppchainValidator {
     }
 }
 
-impl AppchainState {
-    pub fn migrate_validator_state(&self, new_note_of_validator: &String) {
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldValidatorHistory {
+    pub id: ValidatorId,
+    pub set_id: SetId,
+    pub account_id: AccountId,
+    pub weight: Balance,
+    pub block_height: BlockHeight,
+}
+
+impl OldValidatorHistory {
+    pub fn migrate_state(storage_key: &Vec<u8>) {
+        if let Some(data) = env::storage_read(storage_key) {
+            if let Ok(history) = OldValidatorHistory::try_from_slice(&data) {
+                let new_state = ValidatorHistory {
+                    id: history.id.clone(),
+                    set_id: history.set_id,
+                    account_id: history.account_id,
+                    weight: history.weight,
+                    block_height: history.block_height,
+                    self_stake: history.weight,
+                };
+                if let Ok(new_data) = new_state.try_to_vec() {
+                    assert!(
+                        env::storage_write(storage_key, &new_data),
+                        "Migration for history of validator '{}' failed",
+                        &history.id
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldValidatorHistoryIndexSet {
+    pub seq_num: SeqNum,
+    pub set_id: u32,
+    pub indexes: Vec<ValidatorIndex>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldLocked {
+    pub seq_num: SeqNum,
+    pub token_id: AccountId,
+    pub sender_id: AccountId,
+    pub receiver: String,
+    pub amount: U128,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldBurned {
+    pub seq_num: SeqNum,
+    pub sender_id: AccountId,
+    pub receiver: String,
+    pub amount: U128,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub enum OldRawFact {
+    ValidatorHistoryIndexSet(OldValidatorHistoryIndexSet),
+    LockAsset(OldLocked),
+    Burn(OldBurned),
+}
+
+impl OldRawFact {
+    /// The indexes of validator sets are moved to `validator_index_lists`,
+    /// `latest_indexes_set_id` is updated by each validator set in order
+    pub fn migrate_state(
+        storage_key: &Vec<u8>,
+        validator_index_lists: &mut LookupMap<SetId, Vec<ValidatorIndex>>,
+        latest_indexes_set_id: &mut Option<SetId>,
+    ) {
+        if let Some(data) = env::storage_read(storage_key) {
+            if let Ok(fact) = OldRawFact::try_from_slice(&data) {
+                let (seq_num, new_state) = match fact {
+                    OldRawFact::ValidatorHistoryIndexSet(vh_set) => {
+                        // The indexes are only stored if they are changed since the previous set
+                        let indexes_set_id = match *latest_indexes_set_id {
+                            Some(set_id)
+                                if validator_index_lists.get(&set_id).as_ref()
+                                    == Some(&vh_set.indexes) =>
+                            {
+                                set_id
+                            }
+                            _ => {
+                                validator_index_lists.insert(&vh_set.set_id, &vh_set.indexes);
+                                vh_set.set_id
+                            }
+                        };
+                        *latest_indexes_set_id = Some(indexes_set_id);
+                        (
+                            vh_set.seq_num,
+                            RawFact::ValidatorHistoryIndexSet(ValidatorHistoryIndexSet {
+                                seq_num: vh_set.seq_num,
+                                set_id: vh_set.set_id,
+                                indexes_set_id,
+                                validators_len: vh_set.indexes.len() as u32,
+                                timestamp: 0,
+                                staked_balance: None,
+                            }),
+                        )
+                    }
+                    OldRawFact::LockAsset(locked) => (
+                        locked.seq_num,
+                        RawFact::LockAsset(Locked {
+                            seq_num: locked.seq_num,
+                            token_id: locked.token_id,
+                            sender_id: locked.sender_id,
+                            receiver: locked.receiver,
+                            amount: locked.amount,
+                            timestamp: U64::from(0),
+                        }),
+                    ),
+                    OldRawFact::Burn(burned) => (
+                        burned.seq_num,
+                        RawFact::Burn(Burned {
+                            seq_num: burned.seq_num,
+                            sender_id: burned.sender_id,
+                            receiver: burned.receiver,
+                            amount: burned.amount,
+                            timestamp: U64::from(0),
+                        }),
+                    ),
+                };
+                if let Ok(new_data) = new_state.try_to_vec() {
+                    assert!(
+                        env::storage_write(storage_key, &new_data),
+                        "Migration for fact {} failed",
+                        seq_num
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Appchain state of an appchain of Octopus Network
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldAppchainState {
+    pub appchain_id: AppchainId,
+    pub validators: UnorderedMap<ValidatorId, LazyOption<AppchainValidator>>,
+    pub account_map: LookupMap<AccountId, ValidatorId>,
+    pub validators_nonce: u32,
+    pub validators_timestamp: Timestamp,
+    pub validator_set_timestamp: Timestamp,
+    pub booting_timestamp: Timestamp,
+    pub removed_validators: UnorderedMap<ValidatorId, LazyOption<AppchainValidator>>,
+    pub raw_facts: Vector<LazyOption<RawFact>>,
+    pub status: AppchainStatus,
+    pub staked_balance: Balance,
+    pub total_locked_tokens: UnorderedMap<AccountId, u128>,
+    pub upvote_balance: Balance,
+    pub downvote_balance: Balance,
+    pub prover: AppchainProver,
+    pub used_messages: UnorderedMap<u64, bool>,
+    pub validator_history_lists: LookupMap<ValidatorIndex, LazyOption<ValidatorHistoryList>>,
+    pub validator_index_to_id: LookupMap<ValidatorIndex, ValidatorId>,
+    pub validator_last_index: ValidatorIndex,
+    pub validator_id_to_index: LookupMap<ValidatorId, ValidatorIndex>,
+    pub validator_indexes: UnorderedMap<ValidatorIndex, bool>,
+}
+
+impl OldAppchainState {
+    pub fn migrate_state(appchain_id: &AppchainId) {
+        let storage_key = StorageKey::AppchainState(appchain_id.clone()).into_bytes();
+        if let Some(data) = env::storage_read(&storage_key) {
+            if let Ok(state) = OldAppchainState::try_from_slice(&data) {
+                env::log(
+                    format!("Migrating state of appchain '{}'", &state.appchain_id).as_bytes(),
+                );
+                state.migrate_validator_histories();
+                let latest_indexes_set_id = state.migrate_raw_facts();
+                state.migrate_validators();
+                let mut new_state = AppchainState {
+                    appchain_id: state.appchain_id.clone(),
+                    validators: state.validators,
+                    account_map: state.account_map,
+                    validators_nonce: state.validators_nonce,
+                    validators_timestamp: state.validators_timestamp,
+                    validator_set_timestamp: state.validator_set_timestamp,
+                    booting_timestamp: state.booting_timestamp,
+                    removed_validators: state.removed_validators,
+                    raw_facts: state.raw_facts,
+                    status: state.status,
+                    staked_balance: state.staked_balance,
+                    total_locked_tokens: state.total_locked_tokens,
+                    upvote_balance: state.upvote_balance,
+                    downvote_balance: state.downvote_balance,
+                    prover: state.prover,
+                    used_messages: state.used_messages,
+                    validator_history_lists: state.validator_history_lists,
+                    validator_index_to_id: state.validator_index_to_id,
+                    validator_last_index: state.validator_last_index,
+                    validator_id_to_index: state.validator_id_to_index,
+                    validator_indexes: state.validator_indexes,
+                    latest_indexes_set_id,
+                    ..AppchainState::new(appchain_id)
+                };
+                new_state.seed_unlocked_tokens();
+                if let Ok(new_data) = new_state.try_to_vec() {
+                    assert!(
+                        env::storage_write(&storage_key, &new_data),
+                        "Migration for state of appchain '{}' failed",
+                        &state.appchain_id
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn migrate_validators(&self) {
         self.validators.keys_as_vector().iter().for_each(|v| {
-            OldAppchainValidator::migrate_state(&self.id, &v, new_note_of_validator);
+            OldAppchainValidator::migrate_state(&self.appchain_id, &v);
+        });
+        self.removed_validators
+            .keys_as_vector()
+            .iter()
+            .for_each(|v| {
+                OldAppchainValidator::migrate_state(&self.appchain_id, &v);
+            });
+    }
+
+    /// Returns `indexes_set_id` of the latest validator set
+    pub fn migrate_raw_facts(&self) -> Option<SetId> {
+        let mut validator_index_lists =
+            LookupMap::new(StorageKey::ValidatorIndexLists(self.appchain_id.clone()).into_bytes());
+        let mut latest_indexes_set_id = None;
+        (0..self.raw_facts.len() as SeqNum).for_each(|fact_index| {
+            OldRawFact::migrate_state(
+                &StorageKey::RawFact {
+                    appchain_id: self.appchain_id.clone(),
+                    fact_index,
+                }
+                .into_bytes(),
+                &mut validator_index_lists,
+                &mut latest_indexes_set_id,
+            );
         });
-        self.removed_validators.keys_as_vector().iter().for_each(|v| {
-            OldAppchainValidator::migrate_state(&self.id, &v, new_note_of_validator);
+        latest_indexes_set_id
+    }
+
+    pub fn migrate_validator_histories(&self) {
+        (1..=self.validator_last_index).for_each(|validator_index| {
+            if let Some(history_list) = self.validator_history_lists.get(&validator_index) {
+                let history_list = history_list.get().unwrap();
+                (0..history_list.len() as HistoryIndex).for_each(|history_index| {
+                    OldValidatorHistory::migrate_state(
+                        &StorageKey::ValidatorHistory {
+                            appchain_id: self.appchain_id.clone(),
+                            validator_index,
+                            history_index,
+                        }
+                        .into_bytes(),
+                    );
+                });
+            }
         });
     }
 }
 
+/// Struct for relayed bridge token
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldRelayedBridgeToken {
+    token_id: AccountId,
+    symbol: String,
+    bridging_status: BridgingStatus,
+    price: U128,
+    decimals: u32,
+    appchain_permitted: UnorderedMap<AppchainId, bool>,
+}
+
+impl OldRelayedBridgeToken {
+    pub fn migrate_state(token_id: &AccountId) {
+        let storage_key = StorageKey::RelayedBridgeToken {
+            token_id: token_id.clone(),
+        }
+        .into_bytes();
+        if let Some(data) = env::storage_read(&storage_key) {
+            if let Ok(token) = OldRelayedBridgeToken::try_from_slice(&data) {
+                let new_state = RelayedBridgeToken::with_permissions(
+                    token.token_id,
+                    token.symbol,
+                    token.bridging_status,
+                    token.price,
+                    token.decimals,
+                    token.appchain_permitted,
+                );
+                if let Ok(new_data) = new_state.try_to_vec() {
+                    assert!(
+                        env::storage_write(&storage_key, &new_data),
+                        "Migration for bridge token '{}' failed",
+                        token_id
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldOctopusRelay {
+    pub version: u32,
+    pub token_contract_id: AccountId,
+    pub appchain_minimum_validators: u32,
+    pub minimum_staking_amount: Balance,
+    pub total_staked_balance: Balance,
+    pub bridge_limit_ratio: u16,
+    pub owner: AccountId,
+    pub oct_token_price: u128,
+    pub appchain_id_list: Vector<AppchainId>,
+    pub bridge_tokens: UnorderedMap<AccountId, LazyOption<RelayedBridgeToken>>,
+    pub appchain_metadatas: UnorderedMap<AppchainId, LazyOption<AppchainMetadata>>,
+    pub appchain_states: UnorderedMap<AppchainId, LazyOption<AppchainState>>,
+    pub appchain_native_tokens: UnorderedMap<AppchainId, AccountId>,
+}
+
 #[near_bindgen]
 impl OctopusRelay {
     #[init(ignore_state)]
-    pub fn migrate_state(new_note_of_validator: String) -> Self {
+    pub fn migrate_state() -> Self {
         // Deserialize the state using the old contract structure.
-        let old_contract: OctopusRelay = env::state_read().expect("Old state doesn't exist");
+        let old_contract: OldOctopusRelay = env::state_read().expect("Old state doesn't exist");
         // Verify that the migration can only be done by the owner.
         // This is not necessary, if the upgrade is done internally.
         assert_eq!(
@@ -90,18 +524,69 @@ impl OctopusRelay {
             "Can only be called by the owner"
         );
 
-        // Add new field `note` of `AppchainValidator` to old state
+        // Add new field `category` of `AppchainMetadata` to old state
         old_contract
-            .appchain_states
-            .values_as_vector()
+            .appchain_id_list
             .iter()
-            .for_each(|s| {
-                let state = s.get().unwrap();
-                env::log(format!("Migrating state of appchain '{}'", state.id).as_bytes());
-                state.migrate_validator_state(&new_note_of_validator);
-            });
+            .for_each(|appchain_id| OldAppchainMetadata::migrate_state(&appchain_id));
 
-        // Create the new contract using the data from the old contract.
+        // Add new fields of `AppchainState`, `self_stake` of `ValidatorHistory`,
+        // `timestamp` of facts, `staked_balance` of `ValidatorHistoryIndexSet`
+        // and `delegated_total`, `enabled` of `AppchainValidator` to old state
+        old_contract
+            .appchain_id_list
+            .iter()
+            .for_each(|appchain_id| OldAppchainState::migrate_state(&appchain_id));
+
+        // Add new fields `price_updated_at`, `resume_at` and `limit_ratio_override`
+        // of `RelayedBridgeToken` to old state
         old_contract
+            .bridge_tokens
+            .keys_as_vector()
+            .iter()
+            .for_each(|token_id| OldRelayedBridgeToken::migrate_state(&token_id));
+
+        // Create the new contract using the data from the old contract.
+        let mut contract = Self {
+            version: old_contract.version,
+            token_contract_id: old_contract.token_contract_id,
+            appchain_minimum_validators: old_contract.appchain_minimum_validators,
+            minimum_staking_amount: old_contract.minimum_staking_amount,
+            minimum_delegation_amount: old_contract.minimum_staking_amount
+                / DEFAULT_MINIMUM_DELEGATION_DIVISOR,
+            total_staked_balance: old_contract.total_staked_balance,
+            seed_deposit_balance: 0,
+
+            bridge_limit_ratio: old_contract.bridge_limit_ratio,
+            owner: old_contract.owner,
+            oct_token_price: old_contract.oct_token_price,
+            oct_decimals: DEFAULT_OCT_DECIMALS,
+            oct_token_price_updated_at: 0,
+            price_update_cooldown: 0,
+            max_messages_per_relay: DEFAULT_MAX_MESSAGES_PER_RELAY,
+            max_receiver_len: DEFAULT_MAX_RECEIVER_LEN,
+            registration_open: true,
+            min_staging_duration_nanos: 0,
+            validator_set_cycle: DEFAULT_VALIDATOR_SET_CYCLE,
+            staking_globally_paused: true,
+            paused: false,
+
+            appchain_id_list: old_contract.appchain_id_list,
+            bridge_tokens: old_contract.bridge_tokens,
+            appchain_metadatas: old_contract.appchain_metadatas,
+            appchain_states: old_contract.appchain_states,
+            appchain_native_tokens: old_contract.appchain_native_tokens,
+            appchain_categories: UnorderedMap::new(StorageKey::AppchainCategories.into_bytes()),
+            bridge_used_values: LookupMap::new(StorageKey::BridgeUsedValues.into_bytes()),
+        };
+
+        // Compute the total value of bridge tokens locked in each appchain by current locks
+        let appchain_ids: Vec<AppchainId> = contract.appchain_id_list.iter().collect();
+        appchain_ids.iter().for_each(|appchain_id| {
+            let appchain_state = contract.get_appchain_state(appchain_id);
+            let used_val = contract.compute_bridge_used_val(&appchain_state);
+            contract.bridge_used_values.insert(appchain_id, &used_val);
+        });
+        contract
     }
 }
@@ -20,14 +20,57 @@ pub type ValidatorIndex = u32;
 pub type HistoryIndex = u32;
 pub type DelegatorIndex = u32;
 
+/// Type of the public keys used as validator ids of an appchain
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum KeyType {
+    /// Any 32 bytes
+    Raw32,
+    Sr25519,
+    Ed25519,
+}
+
+impl Default for KeyType {
+    fn default() -> Self {
+        KeyType::Raw32
+    }
+}
+
+impl KeyType {
+    /// Parse the key type from its name in messages, `raw32`, `sr25519` or `ed25519`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "raw32" => Some(KeyType::Raw32),
+            "sr25519" => Some(KeyType::Sr25519),
+            "ed25519" => Some(KeyType::Ed25519),
+            _ => None,
+        }
+    }
+}
+
+/// Vote amounts of OCT token of an account to an appchain
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountVote {
+    pub upvote: U128,
+    pub downvote: U128,
+}
+
 /// Describes the status of appchains
+///
+/// An appchain goes `Auditing` -> `Voting` -> `Staging` -> `Booting`, it can only be removed
+/// in `Auditing`. A booting appchain is the active one, it can be frozen by the owner,
+/// which stops staking, bridging and rotation of validator sets.
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug, PartialEq)]
 #[serde(crate = "near_sdk::serde")]
 pub enum AppchainStatus {
     Auditing,
     Voting,
     Staging,
+    /// The appchain is booted and active
+    #[serde(alias = "Active")]
     Booting,
+    Frozen,
 }
 
 impl Default for AppchainStatus {
@@ -53,6 +96,18 @@ pub struct Validator {
     pub staked_amount: U128,
     pub block_height: BlockHeight,
     pub delegators: Vec<Delegator>,
+    pub enabled: bool,
+    pub refund_account: Option<AccountId>,
+    pub key_type: KeyType,
+}
+
+/// Validator to be registered directly by the owner for bootstrapping an appchain
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SeedValidator {
+    pub validator_id: ValidatorId,
+    pub account_id: ValidAccountId,
+    pub amount: U128,
 }
 
 #[derive(Clone, Serialize, Deserialize, BorshDeserialize, BorshSerialize, Debug)]
@@ -60,7 +115,10 @@ pub struct Validator {
 pub struct LiteValidator {
     pub id: ValidatorId,
     pub account_id: AccountId,
+    /// Consensus weight of the validator, equals to `self_stake` plus delegated balance
     pub weight: U128,
+    /// Balance staked by the validator itself
+    pub self_stake: U128,
     pub block_height: BlockHeight,
     pub delegators_len: DelegatorIndex,
 }
@@ -73,6 +131,22 @@ pub struct ValidatorSet {
     pub validators_len: ValidatorIndex,
 }
 
+/// Validators joined or left an appchain in a period
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ValidatorChanges {
+    pub joined: Vec<ValidatorId>,
+    pub left: Vec<ValidatorId>,
+}
+
+/// Current and next validator set of an appchain, read from a single snapshot of state
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ValidatorSetPair {
+    pub current: Option<ValidatorSet>,
+    pub next: Option<ValidatorSet>,
+}
+
 #[derive(Clone, BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Appchain {
@@ -97,6 +171,53 @@ pub struct Appchain {
     pub staked_balance: U128,
     pub subql_url: String,
     pub fact_sets_len: SeqNum,
+    pub category: Option<String>,
+    pub operational_note: Option<String>,
+    pub validator_key_type: KeyType,
+}
+
+/// Overview of validators, stake and bridge utilization of an appchain
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AppchainHealth {
+    pub validator_count: u32,
+    /// Whether the number of validators meets the minimum number of validators
+    pub meets_minimum: bool,
+    pub staked_balance: U128,
+    pub bridge_utilization_bps: u16,
+    pub status: AppchainStatus,
+}
+
+/// Consistency check of the relay-wide staked balance against the sum of all appchains
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StakingDelta {
+    pub relay_total: U128,
+    pub appchains_sum: U128,
+    /// `relay_total - appchains_sum`, zero when the balances are consistent
+    pub delta: I128,
+}
+
+/// Information for clients to connect to a booting appchain
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BootingInfo {
+    pub boot_nodes: String,
+    pub rpc_endpoint: String,
+    pub chain_spec_url: String,
+    pub chain_spec_hash: String,
+    pub chain_spec_raw_url: String,
+    pub chain_spec_raw_hash: String,
+}
+
+/// Chain spec of an appchain for bootstrapping nodes
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ChainSpecInfo {
+    pub url: String,
+    pub hash: String,
+    pub raw_url: String,
+    pub raw_hash: String,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Clone, Debug, PartialEq)]
@@ -131,6 +252,102 @@ pub struct Locked {
     pub sender_id: AccountId,
     pub receiver: String,
     pub amount: U128,
+    /// Block timestamp when the fact was created
+    pub timestamp: U64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LockResult {
+    /// `None` if asset facts of the appchain are not persisted
+    pub seq_num: Option<SeqNum>,
+    pub amount: U128,
+}
+
+/// Gas for cross-contract calls to token contracts in bridging
+#[derive(Clone, Serialize, Deserialize, BorshDeserialize, BorshSerialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GasConfig {
+    /// Gas for `ft_transfer` of bridge token in unlocking
+    pub ft_transfer: U64,
+    /// Gas for `mint` of native token
+    pub mint: U64,
+    /// Gas for `burn` of native token
+    pub burn: U64,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            ft_transfer: FT_TRANSFER_GAS.into(),
+            mint: GAS_FOR_FT_TRANSFER_CALL.into(),
+            burn: GAS_FOR_FT_TRANSFER_CALL.into(),
+        }
+    }
+}
+
+/// Bridging directions enabled for an appchain
+#[derive(Clone, Serialize, Deserialize, BorshDeserialize, BorshSerialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BridgeDirections {
+    /// Whether bridge tokens can be locked for the appchain
+    pub allow_lock: bool,
+    /// Whether native token can be minted on NEAR by `Lock` messages of the appchain
+    pub allow_mint_native: bool,
+    /// Whether native token can be burnt on NEAR to be released on the appchain
+    pub allow_burn_native: bool,
+}
+
+impl Default for BridgeDirections {
+    fn default() -> Self {
+        Self {
+            allow_lock: true,
+            allow_mint_native: true,
+            allow_burn_native: true,
+        }
+    }
+}
+
+/// Tunable parameters of the relay contract
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RelayConfig {
+    pub version: u32,
+    pub token_contract_id: AccountId,
+    pub owner: AccountId,
+    pub appchain_minimum_validators: u32,
+    pub minimum_staking_amount: U128,
+    pub minimum_delegation_amount: U128,
+    pub bridge_limit_ratio: u16,
+    pub oct_token_price: U128,
+    pub price_update_cooldown: U64,
+    pub max_messages_per_relay: u32,
+    pub max_receiver_len: u32,
+    pub registration_open: bool,
+    pub min_staging_duration_nanos: U64,
+    pub validator_set_cycle: U64,
+    pub staking_globally_paused: bool,
+    pub paused: bool,
+}
+
+/// Value of locked bridge tokens against the bridge limit of an appchain
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BridgeUtilization {
+    pub limit_val: U128,
+    pub used_val: U128,
+    /// Ratio of `used_val` to `limit_val` in basis points, capped at 10000
+    pub utilization_bps: u16,
+}
+
+/// Result of auditing the total locked amount of a bridge token
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LockedAudit {
+    /// The total locked amount tracked in state
+    pub tracked: U128,
+    /// The total locked amount recomputed from lock facts and unlocked amount
+    pub from_facts: U128,
 }
 
 #[derive(Clone, Serialize, Deserialize, BorshDeserialize, BorshSerialize, Debug)]
@@ -140,6 +357,8 @@ pub struct Burned {
     pub sender_id: AccountId,
     pub receiver: String,
     pub amount: U128,
+    /// Block timestamp when the fact was created
+    pub timestamp: U64,
 }
 
 #[derive(Clone, Serialize, Deserialize, BorshDeserialize, BorshSerialize, Debug)]
@@ -150,6 +369,30 @@ pub enum Fact {
     Burn(Burned),
 }
 
+/// Fact of an appchain as it is stored, including the indexes of validators in a validator set
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RawFactView {
+    ValidatorHistoryIndexSet {
+        seq_num: SeqNum,
+        set_id: SetId,
+        indexes: Vec<ValidatorIndex>,
+        timestamp: U64,
+        staked_balance: Option<U128>,
+    },
+    LockAsset(Locked),
+    Burn(Burned),
+}
+
+/// Range of facts of an appchain to get in a batch
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FactsRequest {
+    pub appchain_id: AppchainId,
+    pub start: SeqNum,
+    pub limit: SeqNum,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct StorageBalance {
@@ -190,6 +433,36 @@ pub enum MessagePayload {
     Lock(LockPayload),
 }
 
+/// Record of a relayed unlock message which was skipped
+#[derive(Clone, Serialize, Deserialize, BorshDeserialize, BorshSerialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FailedUnlock {
+    pub nonce: u64,
+    pub token_id: AccountId,
+    pub sender: String,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub reason: String,
+}
+
+/// Record of a removal of validator whose refund transfer was failed
+#[derive(Clone, Serialize, Deserialize, BorshDeserialize, BorshSerialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FailedRemoval {
+    pub validator_id: ValidatorId,
+    pub refund_account: AccountId,
+    pub amount: U128,
+}
+
+/// Record of a mint of native token which was failed
+#[derive(Clone, Serialize, Deserialize, BorshDeserialize, BorshSerialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FailedMint {
+    pub nonce: u64,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+}
+
 #[derive(Clone, Serialize, Deserialize, BorshDeserialize, BorshSerialize, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Message {
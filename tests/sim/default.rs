@@ -1,9 +1,14 @@
 use crate::utils::{init, init_by_previous, register_user};
+use codec::{Decode, Encode};
+use near_sdk::borsh::BorshSerialize;
 use near_sdk::json_types::U128;
 use near_sdk::serde_json::json;
+use near_sdk::test_utils::VMContextBuilder;
+use near_sdk::{env, testing_env, MockedBlockchain};
 use near_sdk_sim::{to_yocto, ExecutionResult, UserAccount, DEFAULT_GAS};
 use octopus_relay::types::{
-    Appchain, AppchainStatus, BridgeStatus, BridgeToken, Fact, Validator, ValidatorSet,
+    Appchain, AppchainStatus, BridgeStatus, BridgeToken, BurnAssetPayload, Fact, LockPayload,
+    PayloadType, Validator, ValidatorSet, ValidatorSetPair,
 };
 use num_format::{Locale, ToFormattedString};
 
@@ -144,6 +149,69 @@ pub fn default_stake(
     (outcome, transfer_amount)
 }
 
+pub fn vote(
+    user: &UserAccount,
+    oct: &UserAccount,
+    relay: &UserAccount,
+    vote: &str,
+    amount: u128,
+) -> ExecutionResult {
+    let outcome = user.call(
+        oct.account_id(),
+        "ft_transfer_call",
+        &json!({
+            "receiver_id": relay.valid_account_id(),
+            "amount": U128::from(amount),
+            "msg": format!("vote,testchain,{}", vote),
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        1,
+    );
+    outcome
+}
+
+pub fn default_stake_more(
+    user: &UserAccount,
+    oct: &UserAccount,
+    relay: &UserAccount,
+) -> (ExecutionResult, u128) {
+    let transfer_amount = to_yocto("100");
+    let outcome = user.call(
+        oct.account_id(),
+        "ft_transfer_call",
+        &json!({
+            "receiver_id": relay.valid_account_id(),
+            "amount": transfer_amount.to_string(),
+            "msg": "stake_more,testchain",
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        1,
+    );
+    println!("Gas burnt of function 'ft_transfer_call': {}", outcome.gas_burnt().to_formatted_string(&Locale::en));
+    outcome.assert_success();
+    (outcome, transfer_amount)
+}
+
+pub fn account_exists(root: &UserAccount, relay: &UserAccount, account: &UserAccount) -> bool {
+    let exists: Option<bool> = root
+        .view(
+            relay.account_id(),
+            "account_exists",
+            &json!({
+                "appchain_id": "testchain",
+                "account_id": account.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    exists.unwrap_or(false)
+}
+
 pub fn default_update_appchain(root: &UserAccount, relay: &UserAccount) -> ExecutionResult {
     let chain_spec_url: &str = "https://xxxxxx.xom";
     let chain_spec_hash: &str = "chain_spec_hash";
@@ -274,6 +342,57 @@ pub fn get_facts(root: &UserAccount, relay: &UserAccount) -> Vec<Fact> {
     facts[1..facts.len()].to_vec()
 }
 
+pub fn get_ft_balance(token: &UserAccount, account: &UserAccount) -> U128 {
+    token
+        .view(
+            token.account_id(),
+            "ft_balance_of",
+            &json!({ "account_id": account.valid_account_id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json()
+}
+
+pub fn is_message_used(root: &UserAccount, relay: &UserAccount, nonce: u64) -> bool {
+    root.view(
+        relay.account_id(),
+        "is_message_used",
+        &json!({
+            "appchain_id": "testchain",
+            "nonce": nonce
+        })
+        .to_string()
+        .into_bytes(),
+    )
+    .unwrap_json()
+}
+
+pub fn get_bridge_allowed(root: &UserAccount, relay: &UserAccount, b_token: &UserAccount) -> U128 {
+    root.view(
+        relay.account_id(),
+        "get_bridge_allowed_amount",
+        &json!({
+            "appchain_id": "testchain",
+            "token_id": b_token.valid_account_id()
+        })
+        .to_string()
+        .into_bytes(),
+    )
+    .unwrap_json()
+}
+
+pub fn get_validator_set_pair(root: &UserAccount, relay: &UserAccount) -> ValidatorSetPair {
+    root.view(
+        relay.account_id(),
+        "get_current_and_next_validator_set",
+        &json!({ "appchain_id": "testchain" })
+            .to_string()
+            .into_bytes(),
+    )
+    .unwrap_json()
+}
+
 pub fn lock_token(
     b_token: &UserAccount,
     root: &UserAccount,
@@ -299,3 +418,107 @@ pub fn lock_token(
 
     get_facts(&root, &relay)
 }
+
+/// Message encoded by the appchain, same as `RawMessage` in relay contract
+#[derive(Encode, Decode, Clone)]
+pub struct RawMessage {
+    pub nonce: u64,
+    pub payload_type: PayloadType,
+    pub payload: Vec<u8>,
+}
+
+pub fn burn_asset_message(
+    b_token: &UserAccount,
+    receiver: &UserAccount,
+    amount: u128,
+    nonce: u64,
+) -> RawMessage {
+    RawMessage {
+        nonce,
+        payload_type: PayloadType::BurnAsset,
+        payload: BurnAssetPayload {
+            token_id: b_token.account_id(),
+            sender: "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+                .to_string(),
+            receiver_id: receiver.valid_account_id(),
+            amount: U128::from(amount),
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+pub fn lock_message(receiver: &UserAccount, amount: u128, nonce: u64) -> RawMessage {
+    RawMessage {
+        nonce,
+        payload_type: PayloadType::Lock,
+        payload: LockPayload {
+            sender: "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d"
+                .to_string(),
+            receiver_id: receiver.valid_account_id(),
+            amount: U128::from(amount),
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+/// Return `(header_partial, leaf_proof, mmr_root)` of an MMR of a single leaf
+/// of `encoded_messages`
+pub fn single_leaf_proof(encoded_messages: &[u8]) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    testing_env!(VMContextBuilder::new().build());
+    let header_partial = b"header partial".to_vec();
+    let mut leaf = header_partial.clone();
+    leaf.extend(env::keccak256(encoded_messages));
+    // The root of an MMR of a single leaf is the leaf itself
+    let mmr_root = env::keccak256(&leaf);
+    let leaf_proof = (0u64, 1u64, Vec::<[u8; 32]>::new()).encode();
+    (header_partial, leaf_proof, mmr_root)
+}
+
+/// Set the trusted mmr root to the single leaf of `encoded_messages`,
+/// return the proof for relaying them
+pub fn prove_messages(relay: &UserAccount, encoded_messages: &[u8]) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let (header_partial, leaf_proof, mmr_root) = single_leaf_proof(encoded_messages);
+    relay
+        .call(
+            relay.account_id(),
+            "update_mmr_root",
+            &json!({
+                "appchain_id": "testchain",
+                "mmr_root": mmr_root,
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    (header_partial, leaf_proof, mmr_root)
+}
+
+pub fn relay_messages(
+    relayer: &UserAccount,
+    relay: &UserAccount,
+    messages: Vec<RawMessage>,
+) -> ExecutionResult {
+    let encoded_messages = messages.encode();
+    let (header_partial, leaf_proof, mmr_root) = prove_messages(relay, &encoded_messages);
+    let outcome = relayer.call(
+        relay.account_id(),
+        "relay",
+        &json!({
+            "appchain_id": "testchain",
+            "encoded_messages": encoded_messages,
+            "header_partial": header_partial,
+            "leaf_proof": leaf_proof,
+            "mmr_root": mmr_root,
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        0,
+    );
+    println!("Gas burnt of function 'relay': {}", outcome.gas_burnt().to_formatted_string(&Locale::en));
+    outcome
+}
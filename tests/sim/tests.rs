@@ -1,18 +1,24 @@
 use crate::{
     default::{
-        appchain_minimum_validators, default_activate_appchain, default_appchain_go_staging,
-        default_init, default_init_by_previous, default_pass_appchain, default_register_appchain,
-        default_register_bridge_token, default_set_bridge_permitted, default_stake,
-        default_update_appchain, initial_balance_str, lock_token, minimum_staking_amount_str,
-        to_decimals_amount, val_id0, val_id1,
+        account_exists, appchain_minimum_validators, burn_asset_message, default_activate_appchain,
+        default_appchain_go_staging, default_init, default_init_by_previous, default_pass_appchain,
+        default_register_appchain, default_register_bridge_token, default_set_bridge_permitted,
+        default_stake, default_stake_more, default_update_appchain, get_bridge_allowed, get_facts,
+        get_ft_balance, get_validator_set_pair, initial_balance_str, is_message_used, lock_message,
+        lock_token, minimum_staking_amount_str, prove_messages, relay_messages, single_leaf_proof,
+        to_decimals_amount, val_id0, val_id1, vote,
     },
-    utils::upgrade_contract_code_and_perform_migration,
+    utils::{register_user, upgrade_contract_code_and_perform_migration},
 };
-use near_sdk::json_types::U128;
+use codec::{Decode, Encode};
+use near_sdk::json_types::{Base64VecU8, I128, U128, U64};
 use near_sdk::serde_json::json;
 use near_sdk_sim::{to_yocto, ExecutionResult, UserAccount, DEFAULT_GAS};
 use octopus_relay::types::{
-    Appchain, AppchainStatus, BridgeStatus, BridgeToken, Fact, Validator, ValidatorSet,
+    AccountVote, Appchain, AppchainHealth, AppchainStatus, BootingInfo, BridgeDirections,
+    BridgeStatus, BridgeToken, BridgeUtilization, ChainSpecInfo, Delegator, Fact, FailedMint,
+    FailedRemoval, FailedUnlock, GasConfig, RawFactView, RelayConfig, StakingDelta, Validator,
+    ValidatorChanges, ValidatorSet,
 };
 
 #[test]
@@ -140,7 +146,9 @@ fn simulate_stake() {
             relay.account_id(),
             "get_validators",
             &json!({
-                "appchain_id": "testchain"
+                "appchain_id": "testchain",
+                "from_index": 0,
+                "limit": 10
             })
             .to_string()
             .into_bytes(),
@@ -154,6 +162,57 @@ fn simulate_stake() {
     assert_eq!(validator.staked_amount, U128::from(transfer_amount));
 }
 
+#[test]
+fn simulate_stake_event() {
+    let (root, oct, _, relay, _) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    let (outcome, transfer_amount) = default_stake(&root, &oct, &relay, val_id0);
+    let event = outcome
+        .promise_results()
+        .iter()
+        .flat_map(|r| r.as_ref().unwrap().logs().clone())
+        .find(|l| l.starts_with("EVENT_JSON:"))
+        .unwrap();
+    let event: near_sdk::serde_json::Value =
+        near_sdk::serde_json::from_str(&event["EVENT_JSON:".len()..]).unwrap();
+    assert_eq!(
+        event,
+        json!({
+            "standard": "octopus-relay",
+            "version": "1.0.0",
+            "event": "validator_staked",
+            "data": {
+                "appchain_id": "testchain",
+                "validator_id": val_id0,
+                "account_id": "root",
+                "amount": transfer_amount.to_string(),
+                "event_seq": 1,
+            }
+        })
+    );
+}
+
+#[test]
+fn simulate_event_seq_contiguous() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    let (outcome0, _) = default_stake(&root, &oct, &relay, val_id0);
+    let (outcome1, _) = default_stake(&alice, &oct, &relay, val_id1);
+    let event_seqs: Vec<u64> = vec![outcome0, outcome1]
+        .iter()
+        .flat_map(|outcome| outcome.promise_results())
+        .flat_map(|r| r.as_ref().unwrap().logs().clone())
+        .filter(|l| l.starts_with("EVENT_JSON:"))
+        .map(|l| {
+            let event: near_sdk::serde_json::Value =
+                near_sdk::serde_json::from_str(&l["EVENT_JSON:".len()..]).unwrap();
+            event["data"]["event_seq"].as_u64().unwrap()
+        })
+        .collect();
+    // The registration of the appchain is the event 0
+    assert_eq!(event_seqs, vec![1, 2]);
+}
+
 #[test]
 fn simulate_activate_appchain() {
     let (root, oct, _, relay, alice) = default_init();
@@ -188,13 +247,13 @@ fn simulate_activate_appchain() {
     );
 }
 
-/// Testing for the storage migration, temporarily comment out.
+/// Testing for the storage migration, ignored by default.
 ///
-/// For running this test, you need to manually
-/// rename 'res/octopus_relay.wasm' to 'res/previous_octupus_relay.wasm'
-/// before compile the upgraded relay contract
-///
-// #[test]
+/// For running this test, you need to manually build the relay contract of the previous
+/// version and rename 'res/octopus_relay.wasm' to 'res/previous_octopus_relay.wasm'
+/// before compile the upgraded relay contract, then run it with `cargo test -- --ignored`.
+#[test]
+#[ignore]
 fn test_storage_migration() {
     let (root, oct, _, relay, alice) = default_init_by_previous();
     default_appchain_go_staging(&root, &oct, &relay);
@@ -230,6 +289,54 @@ fn test_storage_migration() {
         appchain.chain_spec_raw_hash,
         String::from("chain_spec_raw_hash")
     );
+
+    // Staking is paused after the migration until it is resumed by the owner.
+    let is_staking_globally_paused = || -> bool {
+        root.view(relay.account_id(), "is_staking_globally_paused", b"")
+            .unwrap_json()
+    };
+    assert!(is_staking_globally_paused());
+    let validator_amount = || -> U128 {
+        let validator: Option<Validator> = root
+            .view(
+                relay.account_id(),
+                "get_validator",
+                &json!({
+                    "appchain_id": "testchain",
+                    "validator_id": val_id1
+                })
+                .to_string()
+                .into_bytes(),
+            )
+            .unwrap_json();
+        validator.unwrap().staked_amount
+    };
+    default_stake_more(&alice, &oct, &relay);
+    assert_eq!(validator_amount(), U128::from(to_yocto("200")));
+    let outcome = alice.call(
+        relay.account_id(),
+        "unstake",
+        &json!({ "appchain_id": "testchain" })
+            .to_string()
+            .into_bytes(),
+        DEFAULT_GAS,
+        0,
+    );
+    assert!(!outcome.is_ok());
+    assert_eq!(validator_amount(), U128::from(to_yocto("200")));
+
+    relay
+        .call(
+            relay.account_id(),
+            "resume_staking_after_migration",
+            &json!({}).to_string().into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    assert!(!is_staking_globally_paused());
+    default_stake_more(&alice, &oct, &relay);
+    assert!(validator_amount().0 > to_yocto("200"));
 }
 
 #[test]
@@ -290,6 +397,81 @@ fn simulate_set_bridge_permitted() {
     );
 }
 
+#[test]
+fn simulate_bridge_allowed_amount_over_limit() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    // Lock near the limit of 266.64 tokens.
+    let facts_len = lock_token(&b_token, &root, &relay, 260).len();
+    assert_eq!(
+        get_bridge_allowed(&root, &relay, &b_token),
+        U128::from(66400 * (10 as u128).pow(12) / 10000)
+    );
+
+    // The used value exceeds the limit after the price of OCT token halves.
+    relay
+        .call(
+            relay.account_id(),
+            "set_oct_token_price",
+            &json!({ "price": U128::from(1000000) })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    assert_eq!(get_bridge_allowed(&root, &relay, &b_token), U128::from(0));
+    // The tokens are returned, no fact of locking is recorded.
+    assert_eq!(lock_token(&b_token, &root, &relay, 1).len(), facts_len);
+}
+
+#[test]
+fn simulate_set_bridge_token_limit_ratio() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    let set_limit_ratio = |user: &UserAccount, ratio: Option<u16>| -> ExecutionResult {
+        user.call(
+            relay.account_id(),
+            "set_bridge_token_limit_ratio",
+            &json!({
+                "token_id": b_token.valid_account_id(),
+                "ratio": ratio
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+
+    assert!(!set_limit_ratio(&root, Some(1000)).is_ok());
+    assert!(!set_limit_ratio(&relay, Some(10001)).is_ok());
+    set_limit_ratio(&relay, Some(1000)).assert_success();
+    let limit_ratio: u16 = root
+        .view(
+            relay.account_id(),
+            "get_bridge_token_limit_ratio",
+            &json!({ "token_id": b_token.valid_account_id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(limit_ratio, 1000);
+    // 400 OCT staked at price 2, 10% of the value is 80 tokens at price 1
+    assert_eq!(
+        get_bridge_allowed(&root, &relay, &b_token),
+        U128::from(80 * (10 as u128).pow(12))
+    );
+
+    set_limit_ratio(&relay, None).assert_success();
+    assert_eq!(
+        get_bridge_allowed(&root, &relay, &b_token),
+        U128::from(2666400 * (10 as u128).pow(12) / 10000)
+    );
+}
+
 #[test]
 fn simulate_lock_token() {
     let (root, oct, b_token, relay, alice) = default_init();
@@ -314,3 +496,3166 @@ fn simulate_lock_token() {
         _ => (),
     }
 }
+
+#[test]
+fn simulate_pause_contract() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    let call_relay = |user: &UserAccount, method_name: &str| -> ExecutionResult {
+        user.call(relay.account_id(), method_name, b"", DEFAULT_GAS, 0)
+    };
+    let is_paused = || -> bool {
+        root.view(relay.account_id(), "is_paused", b"")
+            .unwrap_json()
+    };
+
+    assert!(!call_relay(&root, "pause").is_ok());
+    call_relay(&relay, "pause").assert_success();
+    assert!(is_paused());
+    // The tokens are returned, no fact of locking is recorded.
+    let facts_len = get_facts(&root, &relay).len();
+    assert_eq!(lock_token(&b_token, &root, &relay, 100).len(), facts_len);
+
+    assert!(!call_relay(&root, "unpause").is_ok());
+    call_relay(&relay, "unpause").assert_success();
+    assert!(!is_paused());
+    assert_eq!(
+        lock_token(&b_token, &root, &relay, 100).len(),
+        facts_len + 1
+    );
+}
+
+#[test]
+fn simulate_get_facts_in_time_range() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+
+    lock_token(&b_token, &root, &relay, 100);
+    let locked_events = lock_token(&b_token, &root, &relay, 160);
+    let timestamps: Vec<u64> = locked_events
+        .iter()
+        .map(|fact| match fact {
+            Fact::LockAsset(locked) => locked.timestamp.0,
+            _ => panic!("Unexpected fact type"),
+        })
+        .collect();
+    assert!(timestamps[0] < timestamps[1]);
+
+    let facts: Vec<Fact> = root
+        .view(
+            relay.account_id(),
+            "get_facts_in_time_range",
+            &json!({
+                "appchain_id": "testchain",
+                "from_ts": U64::from(timestamps[0]),
+                "to_ts": U64::from(timestamps[1]),
+                "limit": 100
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(facts.len(), 1);
+    match &facts[0] {
+        Fact::LockAsset(locked) => {
+            assert_eq!(locked.amount, U128::from(to_decimals_amount(100, 12)))
+        }
+        _ => panic!("Unexpected fact type"),
+    }
+}
+
+#[test]
+fn simulate_get_validator_set_scale_encoded() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    let (_, transfer_amount) = default_stake(&root, &oct, &relay, val_id1);
+    default_stake(&alice, &oct, &relay, val_id0);
+    default_activate_appchain(&relay);
+
+    let encoded: Option<Base64VecU8> = root
+        .view(
+            relay.account_id(),
+            "get_validator_set_scale_encoded",
+            &json!({
+                "appchain_id": "testchain",
+                "set_id": 1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    let validators: Vec<([u8; 32], u128)> = Decode::decode(&mut &encoded.unwrap().0[..]).unwrap();
+
+    assert_eq!(validators.len(), 2);
+    assert_eq!(format!("0x{}", hex::encode(validators[0].0)), val_id0);
+    assert_eq!(format!("0x{}", hex::encode(validators[1].0)), val_id1);
+    assert_eq!(validators[0].1, transfer_amount);
+    assert_eq!(validators[1].1, transfer_amount);
+}
+
+#[test]
+fn simulate_prune_used_messages() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    lock_token(&b_token, &root, &relay, 100);
+
+    let message = burn_asset_message(&b_token, &alice, to_decimals_amount(10, 12), 1);
+    relay_messages(&root, &relay, vec![message.clone()]).assert_success();
+
+    relay
+        .call(
+            relay.account_id(),
+            "prune_used_messages",
+            &json!({
+                "appchain_id": "testchain",
+                "below_nonce": 2
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    let message_used = is_message_used(&root, &relay, 1);
+    assert!(message_used);
+
+    let outcome = relay_messages(&root, &relay, vec![message]);
+    assert!(!outcome.is_ok());
+}
+
+#[test]
+fn simulate_max_validators() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    relay
+        .call(
+            relay.account_id(),
+            "set_max_validators",
+            &json!({
+                "appchain_id": "testchain",
+                "max_validators": 1
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    let (_, staked_amount) = default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+    assert!(account_exists(&root, &relay, &root));
+    assert!(!account_exists(&root, &relay, &alice));
+
+    let (_, more_amount) = default_stake_more(&root, &oct, &relay);
+    let validator: Option<Validator> = root
+        .view(
+            relay.account_id(),
+            "get_validator",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id0
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(
+        validator.unwrap().staked_amount,
+        U128::from(staked_amount + more_amount)
+    );
+}
+
+#[test]
+fn simulate_lock_token_returns_seq_num() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    register_user(&relay);
+
+    let outcome = root.call(
+        b_token.account_id(),
+        "ft_transfer_call",
+        &json!({
+            "receiver_id": relay.valid_account_id(),
+            "amount": U128::from(to_decimals_amount(100, 12)),
+            "msg": "lock_token,testchain,receiver",
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS / 2,
+        1,
+    );
+    outcome.assert_success();
+    let log = outcome
+        .promise_results()
+        .iter()
+        .flat_map(|r| r.as_ref().unwrap().logs().clone())
+        .find(|l| l.starts_with("Token locked"))
+        .unwrap();
+
+    let facts = get_facts(&root, &relay);
+    match &facts[0] {
+        Fact::LockAsset(locked) => assert_eq!(
+            log,
+            format!(
+                "Token locked, seq_num is {}, amount is {}.",
+                locked.seq_num,
+                to_decimals_amount(100, 12)
+            )
+        ),
+        _ => panic!("Unexpected fact type"),
+    }
+}
+
+#[test]
+fn simulate_get_account_vote_of_non_voter() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_pass_appchain(&root, &oct, &relay);
+
+    let account_vote: Option<AccountVote> = root
+        .view(
+            relay.account_id(),
+            "get_account_vote",
+            &json!({
+                "appchain_id": "testchain",
+                "account_id": alice.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert!(account_vote.is_none());
+}
+
+#[test]
+fn simulate_admin_seed_validators() {
+    let (root, oct, _, relay, _) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+
+    let amount = to_yocto("200");
+    root.call(
+        oct.account_id(),
+        "ft_transfer_call",
+        &json!({
+            "receiver_id": relay.valid_account_id(),
+            "amount": U128::from(3 * amount),
+            "msg": "seed_deposit",
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        1,
+    )
+    .assert_success();
+
+    let val_id2 = "0x8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48";
+    relay
+        .call(
+            relay.account_id(),
+            "admin_seed_validators",
+            &json!({
+                "appchain_id": "testchain",
+                "entries": [
+                    { "validator_id": val_id0, "account_id": "alice", "amount": U128::from(amount) },
+                    { "validator_id": val_id1, "account_id": "bob", "amount": U128::from(amount) },
+                    { "validator_id": val_id2, "account_id": "carol", "amount": U128::from(amount) },
+                ]
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    let validators: Vec<Validator> = root
+        .view(
+            relay.account_id(),
+            "get_validators",
+            &json!({
+                "appchain_id": "testchain",
+                "from_index": 0,
+                "limit": 10
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(validators.len(), 3);
+    assert!(validators
+        .iter()
+        .all(|v| v.staked_amount == U128::from(amount)));
+
+    let seed_deposit_balance: U128 = root
+        .view(relay.account_id(), "get_seed_deposit_balance", b"")
+        .unwrap_json();
+    assert_eq!(seed_deposit_balance, U128::from(0));
+}
+
+#[test]
+fn simulate_price_update_cooldown() {
+    let (_, _, _, relay, _) = default_init();
+    let set_oct_token_price = |price: u128, force: bool| -> ExecutionResult {
+        relay.call(
+            relay.account_id(),
+            "set_oct_token_price",
+            &json!({
+                "price": U128::from(price),
+                "force": force
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+
+    // 10 seconds
+    relay
+        .call(
+            relay.account_id(),
+            "set_price_update_cooldown",
+            &json!({ "cooldown": U64::from(10_000_000_000) })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    set_oct_token_price(3000000, false).assert_success();
+    assert!(!set_oct_token_price(4000000, false).is_ok());
+    set_oct_token_price(5000000, true).assert_success();
+
+    relay.borrow_runtime_mut().produce_blocks(20).unwrap();
+    set_oct_token_price(6000000, false).assert_success();
+}
+
+#[test]
+fn simulate_appchain_category() {
+    let (root, oct, _, relay, _) = default_init();
+    for category in ["DeFi", "Gaming"].iter() {
+        relay
+            .call(
+                relay.account_id(),
+                "add_category",
+                &json!({ "category": category }).to_string().into_bytes(),
+                DEFAULT_GAS,
+                0,
+            )
+            .assert_success();
+    }
+
+    default_register_appchain(&root, &oct, &relay);
+    root.call(
+        oct.account_id(),
+        "ft_transfer_call",
+        &json!({
+            "receiver_id": relay.valid_account_id(),
+            "amount": U128::from(to_yocto("200")),
+            "msg": "register_appchain,defichain,website_url_string,github_address_string,github_release_string,commit_id,email_string,DeFi",
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        1,
+    )
+    .assert_success();
+    root.call(
+        relay.account_id(),
+        "set_appchain_category",
+        &json!({
+            "appchain_id": "testchain",
+            "category": "Gaming"
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        0,
+    )
+    .assert_success();
+
+    let outcome = root.call(
+        relay.account_id(),
+        "set_appchain_category",
+        &json!({
+            "appchain_id": "testchain",
+            "category": "Unknown"
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        0,
+    );
+    assert!(!outcome.is_ok());
+
+    let get_appchains_by_category = |category: &str| -> Vec<Appchain> {
+        root.view(
+            relay.account_id(),
+            "get_appchains_by_category",
+            &json!({
+                "category": category,
+                "from_index": 0,
+                "limit": 10
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json()
+    };
+    let defi_appchains = get_appchains_by_category("DeFi");
+    assert_eq!(defi_appchains.len(), 1);
+    assert_eq!(defi_appchains[0].id, "defichain");
+    let gaming_appchains = get_appchains_by_category("Gaming");
+    assert_eq!(gaming_appchains.len(), 1);
+    assert_eq!(gaming_appchains[0].id, "testchain");
+    assert_eq!(gaming_appchains[0].category, Some("Gaming".to_string()));
+}
+
+#[test]
+fn simulate_set_staking_requirements() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    let owner_call = |user: &UserAccount, method: &str, args: near_sdk::serde_json::Value| {
+        user.call(
+            relay.account_id(),
+            method,
+            &args.to_string().into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+    assert!(!owner_call(
+        &root,
+        "set_minimum_staking_amount",
+        json!({ "amount": U128::from(to_yocto("300")) })
+    )
+    .is_ok());
+    owner_call(
+        &relay,
+        "set_minimum_staking_amount",
+        json!({ "amount": U128::from(to_yocto("300")) }),
+    )
+    .assert_success();
+    owner_call(&relay, "set_appchain_minimum_validators", json!({ "n": 1 })).assert_success();
+    let relay_config: RelayConfig = root
+        .view(relay.account_id(), "get_relay_config", b"")
+        .unwrap_json();
+    assert_eq!(
+        relay_config.minimum_staking_amount,
+        U128::from(to_yocto("300"))
+    );
+    assert_eq!(relay_config.appchain_minimum_validators, 1);
+
+    // Staking 200 is rejected and refunded.
+    let balance = get_ft_balance(&oct, &alice).0;
+    default_stake(&alice, &oct, &relay, val_id1);
+    assert_eq!(get_ft_balance(&oct, &alice).0, balance);
+
+    owner_call(
+        &relay,
+        "set_minimum_staking_amount",
+        json!({ "amount": U128::from(to_yocto("100")) }),
+    )
+    .assert_success();
+    default_stake(&alice, &oct, &relay, val_id1);
+    assert_eq!(get_ft_balance(&oct, &alice).0, balance - to_yocto("200"));
+}
+
+#[test]
+fn simulate_get_relay_config() {
+    let (root, oct, _, relay, _) = default_init();
+    let relay_config: RelayConfig = root
+        .view(relay.account_id(), "get_relay_config", b"")
+        .unwrap_json();
+    assert_eq!(
+        relay_config,
+        RelayConfig {
+            version: 0,
+            token_contract_id: oct.account_id(),
+            owner: relay.account_id(),
+            appchain_minimum_validators,
+            minimum_staking_amount: U128::from(to_yocto(minimum_staking_amount_str)),
+            minimum_delegation_amount: U128::from(to_yocto("10")),
+            bridge_limit_ratio: 3333,
+            oct_token_price: U128::from(2000000),
+            price_update_cooldown: U64::from(0),
+            max_messages_per_relay: 20,
+            max_receiver_len: 128,
+            registration_open: true,
+            min_staging_duration_nanos: U64::from(0),
+            validator_set_cycle: U64::from(1_200_000_000_000),
+            staking_globally_paused: false,
+            paused: false,
+        }
+    );
+}
+
+#[test]
+fn simulate_relay_skips_invalid_receiver() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    lock_token(&b_token, &root, &relay, 100);
+
+    let amount = to_decimals_amount(10, 12);
+    relay_messages(
+        &root,
+        &relay,
+        vec![
+            burn_asset_message(&b_token, &relay, amount, 1),
+            burn_asset_message(&b_token, &alice, amount, 2),
+        ],
+    )
+    .assert_success();
+
+    assert!(is_message_used(&root, &relay, 1));
+    assert!(is_message_used(&root, &relay, 2));
+
+    let failed_unlocks: Vec<FailedUnlock> = root
+        .view(
+            relay.account_id(),
+            "get_failed_unlocks",
+            &json!({
+                "appchain_id": "testchain",
+                "start": 0,
+                "limit": 10
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(failed_unlocks.len(), 1);
+    assert_eq!(failed_unlocks[0].nonce, 1);
+    assert_eq!(failed_unlocks[0].receiver_id, relay.account_id());
+}
+
+#[test]
+fn simulate_max_messages_per_relay() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    lock_token(&b_token, &root, &relay, 100);
+    relay
+        .call(
+            relay.account_id(),
+            "set_max_messages_per_relay",
+            &json!({ "max_messages_per_relay": 2 })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    let amount = to_decimals_amount(1, 12);
+    let messages: Vec<_> = (1..4)
+        .map(|nonce| burn_asset_message(&b_token, &alice, amount, nonce))
+        .collect();
+    let outcome = relay_messages(&root, &relay, messages);
+    assert!(!outcome.is_ok());
+
+    let message_used = is_message_used(&root, &relay, 1);
+    assert!(!message_used);
+}
+
+#[test]
+fn simulate_failed_mint_is_recorded() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_register_appchain(&root, &oct, &relay);
+    // The native token has no contract deployed, so the mint will fail.
+    relay
+        .call(
+            relay.account_id(),
+            "register_native_token",
+            &json!({
+                "appchain_id": "testchain",
+                "token_id": alice.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    let amount = to_yocto("10");
+    relay_messages(&root, &relay, vec![lock_message(&root, amount, 1)]).assert_success();
+
+    let failed_mints: Vec<FailedMint> = root
+        .view(
+            relay.account_id(),
+            "get_failed_mints",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(failed_mints.len(), 1);
+    assert_eq!(failed_mints[0].nonce, 1);
+    assert_eq!(failed_mints[0].receiver_id, root.account_id());
+    assert_eq!(failed_mints[0].amount, U128::from(amount));
+}
+
+#[test]
+fn simulate_allowed_decimals() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    relay
+        .call(
+            relay.account_id(),
+            "register_bridge_token",
+            &json!({
+                "token_id": alice.valid_account_id(),
+                "symbol": "SIX",
+                "price": U128::from(1000000),
+                "decimals": 6,
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    relay
+        .call(
+            relay.account_id(),
+            "set_allowed_decimals",
+            &json!({
+                "appchain_id": "testchain",
+                "allowed_decimals": [12, 18]
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    let allowed_decimals: Option<(u32, u32)> = root
+        .view(
+            relay.account_id(),
+            "get_allowed_decimals",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(allowed_decimals, Some((12, 18)));
+
+    default_set_bridge_permitted(&b_token, &relay, true);
+    let outcome = relay.call(
+        relay.account_id(),
+        "set_bridge_permitted",
+        &json!({
+            "token_id": alice.valid_account_id(),
+            "appchain_id": "testchain",
+            "permitted": true
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        0,
+    );
+    assert!(!outcome.is_ok());
+}
+
+#[test]
+fn simulate_get_current_and_next_validator_set() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+
+    let validator_set_pair = get_validator_set_pair(&root, &relay);
+    let current = validator_set_pair.current.unwrap();
+    assert!(validator_set_pair.next.is_none());
+
+    // Validators changed, but the cycle is not over yet.
+    default_stake_more(&root, &oct, &relay);
+    assert!(get_validator_set_pair(&root, &relay).next.is_none());
+
+    // Wait for more than a cycle of validator set (20 minutes).
+    root.borrow_runtime_mut().produce_blocks(1300).unwrap();
+    let validator_set_pair = get_validator_set_pair(&root, &relay);
+    assert_eq!(validator_set_pair.current.unwrap().set_id, current.set_id);
+    assert_eq!(validator_set_pair.next.unwrap().set_id, current.set_id + 1);
+}
+
+#[test]
+fn simulate_set_validator_set_cycle() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    let set_validator_set_cycle = |user: &UserAccount, nanoseconds: u128| -> ExecutionResult {
+        user.call(
+            relay.account_id(),
+            "set_validator_set_cycle",
+            &json!({ "nanoseconds": U128::from(nanoseconds) })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+    // 1 minute
+    assert!(!set_validator_set_cycle(&root, 60_000_000_000).is_ok());
+    assert!(!set_validator_set_cycle(&relay, 0).is_ok());
+    set_validator_set_cycle(&relay, 60_000_000_000).assert_success();
+    let validator_set_cycle: U64 = root
+        .view(relay.account_id(), "get_validator_set_cycle", b"")
+        .unwrap_json();
+    assert_eq!(validator_set_cycle, U64::from(60_000_000_000));
+
+    // The new cycle applies to the appchain registered after it is set.
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_stake_more(&root, &oct, &relay);
+    assert!(get_validator_set_pair(&root, &relay).next.is_none());
+
+    // Wait for more than the new cycle of validator set.
+    root.borrow_runtime_mut().produce_blocks(70).unwrap();
+    assert!(get_validator_set_pair(&root, &relay).next.is_some());
+}
+
+#[test]
+fn simulate_mint_native_token_refunds_excess_deposit() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    relay
+        .call(
+            relay.account_id(),
+            "register_native_token",
+            &json!({
+                "appchain_id": "testchain",
+                "token_id": alice.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    let encoded_messages = vec![lock_message(&root, to_yocto("10"), 1)].encode();
+    let (header_partial, leaf_proof, mmr_root) = prove_messages(&relay, &encoded_messages);
+    let balance_before = root.account().unwrap().amount;
+    root.call(
+        relay.account_id(),
+        "relay",
+        &json!({
+            "appchain_id": "testchain",
+            "encoded_messages": encoded_messages,
+            "header_partial": header_partial,
+            "leaf_proof": leaf_proof,
+            "mmr_root": mmr_root,
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        to_yocto("1"),
+    )
+    .assert_success();
+    let balance_after = root.account().unwrap().amount;
+
+    // Only the storage deposit (0.00125 NEAR) and gas are spent.
+    assert!(balance_before - balance_after < to_yocto("0.1"));
+}
+
+#[test]
+fn simulate_get_appchains_needing_rotation() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    alice
+        .call(
+            oct.account_id(),
+            "ft_transfer_call",
+            &json!({
+                "receiver_id": relay.valid_account_id(),
+                "amount": U128::from(to_yocto("200")),
+                "msg": "register_appchain,otherchain,website_url_string,github_address_string,github_release_string,commit_id,email_string",
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            1,
+        )
+        .assert_success();
+
+    let get_appchains_needing_rotation = || -> Vec<String> {
+        root.view(
+            relay.account_id(),
+            "get_appchains_needing_rotation",
+            &json!({
+                "from_index": 0,
+                "limit": 10
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json()
+    };
+    default_stake_more(&root, &oct, &relay);
+    assert!(get_appchains_needing_rotation().is_empty());
+
+    // Wait for more than a cycle of validator set (20 minutes).
+    root.borrow_runtime_mut().produce_blocks(1300).unwrap();
+    assert_eq!(
+        get_appchains_needing_rotation(),
+        vec!["testchain".to_string()]
+    );
+}
+
+#[test]
+fn simulate_close_registration() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    relay
+        .call(
+            relay.account_id(),
+            "set_registration_open",
+            &json!({ "open": false }).to_string().into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    let registration_open: bool = root
+        .view(relay.account_id(), "is_registration_open", b"")
+        .unwrap_json();
+    assert!(!registration_open);
+
+    let balance_before = get_ft_balance(&oct, &alice);
+    alice
+        .call(
+            oct.account_id(),
+            "ft_transfer_call",
+            &json!({
+                "receiver_id": relay.valid_account_id(),
+                "amount": U128::from(to_yocto("200")),
+                "msg": "register_appchain,otherchain,website_url_string,github_address_string,github_release_string,commit_id,email_string",
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            1,
+        )
+        .assert_success();
+    assert_eq!(get_ft_balance(&oct, &alice), balance_before);
+    let num_appchains: u32 = root
+        .view(relay.account_id(), "get_num_appchains", b"")
+        .unwrap_json();
+    assert_eq!(num_appchains, 1);
+
+    default_stake(&root, &oct, &relay, val_id0);
+    assert!(account_exists(&root, &relay, &root));
+}
+
+#[test]
+fn simulate_appchain_note() {
+    let (root, oct, _, relay, _) = default_init();
+    default_register_appchain(&root, &oct, &relay);
+    let set_appchain_note = |user: &UserAccount, note: Option<&str>| -> ExecutionResult {
+        user.call(
+            relay.account_id(),
+            "set_appchain_note",
+            &json!({
+                "appchain_id": "testchain",
+                "note": note
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+    let get_note = || -> Option<String> {
+        let appchain: Option<Appchain> = root
+            .view(
+                relay.account_id(),
+                "get_appchain",
+                &json!({ "appchain_id": "testchain" })
+                    .to_string()
+                    .into_bytes(),
+            )
+            .unwrap_json();
+        appchain.unwrap().operational_note
+    };
+
+    set_appchain_note(&relay, Some("maintenance until 14:00 UTC")).assert_success();
+    assert_eq!(get_note(), Some("maintenance until 14:00 UTC".to_string()));
+
+    let too_long_note = "x".repeat(257);
+    assert!(!set_appchain_note(&root, Some(too_long_note.as_str())).is_ok());
+
+    set_appchain_note(&root, None).assert_success();
+    assert_eq!(get_note(), None);
+}
+
+#[test]
+fn simulate_appchain_gas_override() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_register_appchain(&root, &oct, &relay);
+    alice
+        .call(
+            oct.account_id(),
+            "ft_transfer_call",
+            &json!({
+                "receiver_id": relay.valid_account_id(),
+                "amount": U128::from(to_yocto("200")),
+                "msg": "register_appchain,otherchain,website_url_string,github_address_string,github_release_string,commit_id,email_string",
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            1,
+        )
+        .assert_success();
+    let get_gas_config = |appchain_id: &str| -> GasConfig {
+        root.view(
+            relay.account_id(),
+            "get_appchain_gas_config",
+            &json!({ "appchain_id": appchain_id })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json()
+    };
+    let default_gas_config = get_gas_config("testchain");
+    let gas_override = GasConfig {
+        ft_transfer: U64::from(20_000_000_000_000),
+        mint: U64::from(60_000_000_000_000),
+        burn: U64::from(60_000_000_000_000),
+    };
+    let set_gas_override = |user: &UserAccount| -> ExecutionResult {
+        user.call(
+            relay.account_id(),
+            "set_appchain_gas_override",
+            &json!({
+                "appchain_id": "testchain",
+                "config": gas_override
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+    assert!(!set_gas_override(&root).is_ok());
+    set_gas_override(&relay).assert_success();
+    assert_eq!(get_gas_config("testchain"), gas_override);
+    assert_eq!(get_gas_config("otherchain"), default_gas_config);
+
+    // The mint with larger gas is still executed, and fails for no contract deployed.
+    relay
+        .call(
+            relay.account_id(),
+            "register_native_token",
+            &json!({
+                "appchain_id": "testchain",
+                "token_id": alice.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    relay_messages(&root, &relay, vec![lock_message(&root, to_yocto("10"), 1)]).assert_success();
+    let failed_mints: Vec<FailedMint> = root
+        .view(
+            relay.account_id(),
+            "get_failed_mints",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(failed_mints.len(), 1);
+}
+
+#[test]
+fn simulate_get_raw_facts() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    default_stake(&root, &oct, &relay, val_id1);
+    default_stake(&alice, &oct, &relay, val_id0);
+    default_activate_appchain(&relay);
+
+    let raw_facts: Vec<RawFactView> = root
+        .view(
+            relay.account_id(),
+            "get_raw_facts",
+            &json!({
+                "appchain_id": "testchain",
+                "start": 0,
+                "limit": 10
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(raw_facts.len(), 1);
+    if let RawFactView::ValidatorHistoryIndexSet {
+        seq_num,
+        set_id,
+        indexes,
+        ..
+    } = &raw_facts[0]
+    {
+        assert_eq!(*seq_num, 0);
+        assert_eq!(*set_id, 1);
+        let mut indexes = indexes.clone();
+        indexes.sort();
+        assert_eq!(indexes, vec![1, 2]);
+    } else {
+        panic!("The first fact should be a validator set.");
+    }
+}
+
+#[test]
+fn simulate_stake_to_nonexistent_appchain() {
+    let (root, oct, _, relay, _) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    let balance_before = get_ft_balance(&oct, &root);
+    for msg in vec![
+        format!("stake,nochain,{}", val_id0),
+        "stake_more,nochain".to_string(),
+    ] {
+        root.call(
+            oct.account_id(),
+            "ft_transfer_call",
+            &json!({
+                "receiver_id": relay.valid_account_id(),
+                "amount": U128::from(to_yocto("200")),
+                "msg": msg,
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            1,
+        )
+        .assert_success();
+    }
+    assert_eq!(get_ft_balance(&oct, &root), balance_before);
+    assert!(!account_exists(&root, &relay, &root));
+}
+
+#[test]
+fn simulate_relay_refunds_unused_deposit() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    lock_token(&b_token, &root, &relay, 100);
+
+    // The receiver is already registered in the bridge token contract.
+    let messages = vec![
+        burn_asset_message(&b_token, &alice, to_decimals_amount(10, 12), 1),
+        burn_asset_message(&b_token, &alice, to_decimals_amount(10, 12), 2),
+    ];
+    let encoded_messages = messages.encode();
+    let (header_partial, leaf_proof, mmr_root) = prove_messages(&relay, &encoded_messages);
+    let balance_before = root.account().unwrap().amount;
+    root.call(
+        relay.account_id(),
+        "relay",
+        &json!({
+            "appchain_id": "testchain",
+            "encoded_messages": encoded_messages,
+            "header_partial": header_partial,
+            "leaf_proof": leaf_proof,
+            "mmr_root": mmr_root,
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        to_yocto("1"),
+    )
+    .assert_success();
+    let balance_after = root.account().unwrap().amount;
+
+    // Only gas is spent.
+    assert!(balance_before - balance_after < to_yocto("0.1"));
+}
+
+#[test]
+fn simulate_get_staked_balance_at_set() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    let get_staked_balance_at_set = |set_id: u32| -> Option<U128> {
+        root.view(
+            relay.account_id(),
+            "get_staked_balance_at_set",
+            &json!({
+                "appchain_id": "testchain",
+                "set_id": set_id
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json()
+    };
+
+    let balance_at_set1 = get_staked_balance_at_set(1).unwrap();
+    assert!(get_staked_balance_at_set(2).is_none());
+
+    default_stake_more(&root, &oct, &relay);
+    // Wait for more than a cycle of validator set (20 minutes),
+    // the next validator set is created before locking token.
+    root.borrow_runtime_mut().produce_blocks(1300).unwrap();
+    lock_token(&b_token, &root, &relay, 10);
+
+    let balance_at_set2 = get_staked_balance_at_set(2).unwrap();
+    assert!(balance_at_set2.0 > balance_at_set1.0);
+    assert_eq!(get_staked_balance_at_set(1), Some(balance_at_set1));
+}
+
+#[test]
+fn simulate_min_staging_duration() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    // 10 minutes
+    relay
+        .call(
+            relay.account_id(),
+            "set_min_staging_duration",
+            &json!({ "duration": U64::from(600_000_000_000) })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+
+    let activate_appchain = || -> ExecutionResult {
+        relay.call(
+            relay.account_id(),
+            "activate_appchain",
+            &json!({
+                "appchain_id": "testchain",
+                "boot_nodes": "[]",
+                "rpc_endpoint": "rpc_endpoint",
+                "chain_spec_url": "chain_spec_url",
+                "chain_spec_hash": "chain_spec_hash",
+                "chain_spec_raw_url": "chain_spec_raw_url",
+                "chain_spec_raw_hash": "chain_spec_raw_hash",
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+    assert!(!activate_appchain().is_ok());
+
+    root.borrow_runtime_mut().produce_blocks(700).unwrap();
+    activate_appchain().assert_success();
+}
+
+#[test]
+fn simulate_get_bridge_utilization() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    let get_bridge_utilization = || -> BridgeUtilization {
+        root.view(
+            relay.account_id(),
+            "get_bridge_utilization",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json()
+    };
+    let utilization = get_bridge_utilization();
+    assert_eq!(utilization.used_val, U128::from(0));
+    assert_eq!(utilization.utilization_bps, 0);
+
+    lock_token(&b_token, &root, &relay, 100);
+    let utilization = get_bridge_utilization();
+    // 100 tokens with price 1000000
+    assert_eq!(utilization.used_val, U128::from(100 * 1000000));
+    assert_eq!(
+        utilization.utilization_bps as u128,
+        utilization.used_val.0 * 10000 / utilization.limit_val.0
+    );
+    assert!(utilization.utilization_bps > 0);
+}
+
+#[test]
+fn simulate_set_validator_enabled() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    let set_validator_enabled = |enabled: bool| {
+        relay
+            .call(
+                relay.account_id(),
+                "set_validator_enabled",
+                &json!({
+                    "appchain_id": "testchain",
+                    "validator_id": val_id1,
+                    "enabled": enabled
+                })
+                .to_string()
+                .into_bytes(),
+                DEFAULT_GAS,
+                0,
+            )
+            .assert_success();
+    };
+    let get_next_validators_len = || -> u32 {
+        get_validator_set_pair(&root, &relay)
+            .next
+            .unwrap()
+            .validators_len
+    };
+
+    set_validator_enabled(false);
+    // Wait for more than a cycle of validator set (20 minutes).
+    root.borrow_runtime_mut().produce_blocks(1300).unwrap();
+    assert_eq!(get_next_validators_len(), 1);
+    let validator: Option<Validator> = root
+        .view(
+            relay.account_id(),
+            "get_validator",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    let validator = validator.unwrap();
+    assert!(!validator.enabled);
+    assert_eq!(validator.staked_amount, U128::from(to_yocto("200")));
+
+    set_validator_enabled(true);
+    root.borrow_runtime_mut().produce_blocks(1300).unwrap();
+    assert_eq!(get_next_validators_len(), 2);
+}
+
+#[test]
+fn simulate_get_validators_for_account() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    alice
+        .call(
+            oct.account_id(),
+            "ft_transfer_call",
+            &json!({
+                "receiver_id": relay.valid_account_id(),
+                "amount": U128::from(to_yocto("200")),
+                "msg": "register_appchain,otherchain,website_url_string,github_address_string,github_release_string,commit_id,email_string",
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            1,
+        )
+        .assert_success();
+
+    let validators: Vec<(String, Option<Validator>)> = root
+        .view(
+            relay.account_id(),
+            "get_validators_for_account",
+            &json!({
+                "account_id": alice.valid_account_id(),
+                "appchain_ids": ["testchain", "otherchain"]
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(validators.len(), 2);
+    assert_eq!(validators[0].0, "testchain");
+    assert_eq!(validators[0].1.as_ref().unwrap().id, val_id1);
+    assert_eq!(validators[1].0, "otherchain");
+    assert!(validators[1].1.is_none());
+}
+
+#[test]
+fn simulate_get_appchain_booting_info() {
+    let (root, oct, _, relay, alice) = default_init();
+    let get_appchain_booting_info = || -> Option<BootingInfo> {
+        root.view(
+            relay.account_id(),
+            "get_appchain_booting_info",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json()
+    };
+
+    default_appchain_go_staging(&root, &oct, &relay);
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+    assert!(get_appchain_booting_info().is_none());
+
+    default_activate_appchain(&relay);
+    let booting_info = get_appchain_booting_info().unwrap();
+    assert!(booting_info.boot_nodes.contains(
+        "/ip4/13.230.75.107/tcp/30333/p2p/12D3KooWAxYKgdmTczLioD1jkzMyaDuV2Q5VHBsJxPr5zEmHr8nY"
+    ));
+    assert_eq!(
+        booting_info.rpc_endpoint,
+        "wss://barnacle.rpc.testnet.oct.network:9944"
+    );
+    assert_eq!(booting_info.chain_spec_url, "chain_spec_url");
+    assert_eq!(booting_info.chain_spec_hash, "chain_spec_hash");
+    assert_eq!(booting_info.chain_spec_raw_url, "chain_spec_raw_url");
+    assert_eq!(booting_info.chain_spec_raw_hash, "chain_spec_raw_hash");
+}
+
+#[test]
+fn simulate_admin_adjust_validator_stake() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+    let adjust_validator_stake = |new_amount: &str| {
+        relay.call(
+            relay.account_id(),
+            "admin_adjust_validator_stake",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id1,
+                "new_amount": U128::from(to_yocto(new_amount))
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+    let assert_balances = |validator_amount: &str, staked_balance: &str| {
+        let validator: Option<Validator> = root
+            .view(
+                relay.account_id(),
+                "get_validator",
+                &json!({
+                    "appchain_id": "testchain",
+                    "validator_id": val_id1
+                })
+                .to_string()
+                .into_bytes(),
+            )
+            .unwrap_json();
+        assert_eq!(
+            validator.unwrap().staked_amount,
+            U128::from(to_yocto(validator_amount))
+        );
+        let appchain: Option<Appchain> = root
+            .view(
+                relay.account_id(),
+                "get_appchain",
+                &json!({ "appchain_id": "testchain" })
+                    .to_string()
+                    .into_bytes(),
+            )
+            .unwrap_json();
+        assert_eq!(
+            appchain.unwrap().staked_balance,
+            U128::from(to_yocto(staked_balance))
+        );
+        let total_staked_balance: U128 = root
+            .view(relay.account_id(), "get_total_staked_balance", b"")
+            .unwrap_json();
+        assert_eq!(total_staked_balance, U128::from(to_yocto(staked_balance)));
+    };
+    assert_balances("200", "400");
+
+    adjust_validator_stake("250").assert_success();
+    assert_balances("250", "450");
+
+    adjust_validator_stake("150").assert_success();
+    assert_balances("150", "350");
+
+    // Below the minimum staking amount, the validator should be removed instead.
+    let outcome = adjust_validator_stake("50");
+    assert!(!outcome.is_ok());
+    assert_balances("150", "350");
+}
+
+#[test]
+fn simulate_get_validator_changes_since() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+    default_activate_appchain(&relay);
+    let raw_facts: Vec<RawFactView> = root
+        .view(
+            relay.account_id(),
+            "get_raw_facts",
+            &json!({
+                "appchain_id": "testchain",
+                "start": 0,
+                "limit": 1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    let since_ts = match &raw_facts[0] {
+        RawFactView::ValidatorHistoryIndexSet { timestamp, .. } => timestamp.0,
+        _ => panic!("The first fact should be a validator set."),
+    };
+    let get_validator_changes_since = || -> ValidatorChanges {
+        root.view(
+            relay.account_id(),
+            "get_validator_changes_since",
+            &json!({
+                "appchain_id": "testchain",
+                "since_ts": U64::from(since_ts)
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json()
+    };
+    let validator_changes = get_validator_changes_since();
+    assert!(validator_changes.joined.is_empty());
+    assert!(validator_changes.left.is_empty());
+
+    // Bob joins and alice leaves in the next validator set.
+    let bob = root.create_user("bob".to_string(), to_yocto("100"));
+    register_user(&bob);
+    root.call(
+        oct.account_id(),
+        "ft_transfer",
+        &json!({
+            "receiver_id": bob.valid_account_id(),
+            "amount": U128::from(to_yocto("200")),
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        1,
+    )
+    .assert_success();
+    let val_id2 = "0x8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48";
+    default_stake(&bob, &oct, &relay, val_id2);
+    alice
+        .call(
+            relay.account_id(),
+            "unstake",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    // Wait for more than a cycle of validator set (20 minutes),
+    // the next validator set is created by the next staking action.
+    root.borrow_runtime_mut().produce_blocks(1300).unwrap();
+    default_stake_more(&root, &oct, &relay);
+
+    let validator_changes = get_validator_changes_since();
+    assert_eq!(validator_changes.joined, vec![val_id2.to_string()]);
+    assert_eq!(validator_changes.left, vec![val_id1.to_string()]);
+}
+
+#[test]
+fn simulate_unstake_to_refund_account() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+    let bob = root.create_user("bob".to_string(), to_yocto("100"));
+    register_user(&bob);
+    let alice_balance_before = get_ft_balance(&oct, &alice);
+
+    alice
+        .call(
+            relay.account_id(),
+            "set_validator_refund_account",
+            &json!({
+                "appchain_id": "testchain",
+                "account": bob.valid_account_id()
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    alice
+        .call(
+            relay.account_id(),
+            "unstake",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    assert_eq!(get_ft_balance(&oct, &bob), U128::from(to_yocto("200")));
+    assert_eq!(get_ft_balance(&oct, &alice), alice_balance_before);
+}
+
+#[test]
+fn simulate_unstake_refunds_once() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+    let unstake = || -> ExecutionResult {
+        alice.call(
+            relay.account_id(),
+            "unstake",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+    let balance_before = get_ft_balance(&oct, &alice);
+
+    unstake().assert_success();
+    assert_eq!(
+        get_ft_balance(&oct, &alice).0,
+        balance_before.0 + to_yocto("200")
+    );
+
+    // Nothing is left to withdraw for the removed validator.
+    assert!(!unstake().is_ok());
+    assert!(!relay
+        .call(
+            relay.account_id(),
+            "retry_remove_validator",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id1
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .is_ok());
+    assert_eq!(
+        get_ft_balance(&oct, &alice).0,
+        balance_before.0 + to_yocto("200")
+    );
+}
+
+#[test]
+fn simulate_get_facts_multi() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    lock_token(&b_token, &root, &relay, 100);
+    lock_token(&b_token, &root, &relay, 160);
+    alice
+        .call(
+            oct.account_id(),
+            "ft_transfer_call",
+            &json!({
+                "receiver_id": relay.valid_account_id(),
+                "amount": U128::from(to_yocto("200")),
+                "msg": "register_appchain,otherchain,website_url_string,github_address_string,github_release_string,commit_id,email_string",
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            1,
+        )
+        .assert_success();
+
+    let facts_multi: Vec<Vec<Fact>> = root
+        .view(
+            relay.account_id(),
+            "get_facts_multi",
+            &json!({
+                "requests": [
+                    { "appchain_id": "testchain", "start": 1, "limit": 10 },
+                    { "appchain_id": "otherchain", "start": 0, "limit": 10 },
+                    { "appchain_id": "nochain", "start": 0, "limit": 10 },
+                ]
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(facts_multi.len(), 3);
+    assert_eq!(facts_multi[0].len(), 2);
+    assert!(facts_multi[0]
+        .iter()
+        .all(|fact| matches!(fact, Fact::LockAsset(_))));
+    assert!(facts_multi[1].is_empty());
+    assert!(facts_multi[2].is_empty());
+}
+
+#[test]
+fn simulate_get_appchain_health() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    lock_token(&b_token, &root, &relay, 100);
+
+    let health: AppchainHealth = root
+        .view(
+            relay.account_id(),
+            "get_appchain_health",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    let utilization: BridgeUtilization = root
+        .view(
+            relay.account_id(),
+            "get_bridge_utilization",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(health.validator_count, 2);
+    assert!(health.meets_minimum);
+    assert_eq!(health.staked_balance, U128::from(to_yocto("400")));
+    assert_eq!(health.bridge_utilization_bps, utilization.utilization_bps);
+    assert!(health.bridge_utilization_bps > 0);
+    assert_eq!(health.status, AppchainStatus::Booting);
+}
+
+#[test]
+fn simulate_get_appchain_chain_spec() {
+    let (root, oct, _, relay, alice) = default_init();
+    let get_appchain_chain_spec = || -> Option<ChainSpecInfo> {
+        root.view(
+            relay.account_id(),
+            "get_appchain_chain_spec",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json()
+    };
+
+    default_appchain_go_staging(&root, &oct, &relay);
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+    assert!(get_appchain_chain_spec().is_none());
+
+    default_activate_appchain(&relay);
+    assert_eq!(
+        get_appchain_chain_spec(),
+        Some(ChainSpecInfo {
+            url: "chain_spec_url".to_string(),
+            hash: "chain_spec_hash".to_string(),
+            raw_url: "chain_spec_raw_url".to_string(),
+            raw_hash: "chain_spec_raw_hash".to_string(),
+        })
+    );
+}
+
+#[test]
+fn simulate_set_id_for_timestamp() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+    default_activate_appchain(&relay);
+    let get_set_timestamps = || -> Vec<(u32, u64)> {
+        let raw_facts: Vec<RawFactView> = root
+            .view(
+                relay.account_id(),
+                "get_raw_facts",
+                &json!({
+                    "appchain_id": "testchain",
+                    "start": 0,
+                    "limit": 10
+                })
+                .to_string()
+                .into_bytes(),
+            )
+            .unwrap_json();
+        raw_facts
+            .iter()
+            .filter_map(|raw_fact| match raw_fact {
+                RawFactView::ValidatorHistoryIndexSet {
+                    set_id, timestamp, ..
+                } => Some((*set_id, timestamp.0)),
+                _ => None,
+            })
+            .collect()
+    };
+    let set_id_for_timestamp = |timestamp: u64| -> Option<u32> {
+        root.view(
+            relay.account_id(),
+            "set_id_for_timestamp",
+            &json!({
+                "appchain_id": "testchain",
+                "timestamp": U64::from(timestamp)
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json()
+    };
+    // 20 minutes
+    let cycle: u64 = 20 * 60_000_000_000;
+    let (first_set_id, booting_timestamp) = get_set_timestamps()[0];
+    assert_eq!(first_set_id, 1);
+    assert_eq!(set_id_for_timestamp(booting_timestamp - 1), None);
+
+    // The change in the first cycle takes effect in the next cycle.
+    default_stake_more(&root, &oct, &relay);
+    root.borrow_runtime_mut().produce_blocks(1300).unwrap();
+    assert_eq!(set_id_for_timestamp(booting_timestamp + cycle), Some(2));
+
+    // The next validator set is created by the next staking action.
+    default_stake_more(&root, &oct, &relay);
+    let set_timestamps = get_set_timestamps();
+    assert_eq!(set_timestamps.len(), 2);
+    let (second_set_id, second_set_timestamp) = set_timestamps[1];
+    assert_eq!(second_set_id, 2);
+
+    let set_ids: Vec<Option<u32>> = vec![
+        booting_timestamp,
+        booting_timestamp + cycle - 1,
+        booting_timestamp + cycle,
+        second_set_timestamp,
+    ]
+    .into_iter()
+    .map(|timestamp| set_id_for_timestamp(timestamp))
+    .collect();
+    assert_eq!(set_ids, vec![Some(1), Some(1), Some(2), Some(2)]);
+}
+
+#[test]
+fn simulate_relay_to_staging_appchain() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+
+    let outcome = relay_messages(&root, &relay, vec![lock_message(&alice, 100, 0)]);
+    assert!(!outcome.is_ok());
+    let message_used = is_message_used(&root, &relay, 0);
+    assert!(!message_used);
+}
+
+#[test]
+fn simulate_get_staking_balance_delta() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+    default_stake_more(&root, &oct, &relay);
+
+    let staking_delta: StakingDelta = root
+        .view(relay.account_id(), "get_staking_balance_delta", &[])
+        .unwrap_json();
+    assert_eq!(staking_delta.relay_total, U128::from(to_yocto("500")));
+    assert_eq!(staking_delta.appchains_sum, staking_delta.relay_total);
+    assert_eq!(staking_delta.delta, I128::from(0));
+}
+
+#[test]
+fn simulate_pause_bridge_token_with_timeout() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    register_user(&relay);
+    let try_lock_token = || -> usize {
+        root.call(
+            b_token.account_id(),
+            "ft_transfer_call",
+            &json!({
+                "receiver_id": relay.valid_account_id(),
+                "amount": U128::from(to_decimals_amount(10, 12)),
+                "msg": "lock_token,testchain,receiver",
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS / 2,
+            1,
+        )
+        .assert_success();
+        get_facts(&root, &relay).len()
+    };
+    let get_bridge_status = || -> BridgeStatus {
+        let bridge_token: Option<BridgeToken> = root
+            .view(
+                relay.account_id(),
+                "get_bridge_token",
+                &json!({ "token_id": b_token.valid_account_id() })
+                    .to_string()
+                    .into_bytes(),
+            )
+            .unwrap_json();
+        bridge_token.unwrap().status
+    };
+
+    relay
+        .call(
+            relay.account_id(),
+            "pause_bridge_token",
+            &json!({
+                "token_id": b_token.valid_account_id(),
+                "resume_after_nanos": U64::from(60_000_000_000),
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    assert_eq!(get_bridge_status(), BridgeStatus::Paused);
+    let facts_len = get_facts(&root, &relay).len();
+    assert_eq!(try_lock_token(), facts_len);
+
+    root.borrow_runtime_mut().produce_blocks(100).unwrap();
+    assert_eq!(get_bridge_status(), BridgeStatus::Active);
+    assert_eq!(try_lock_token(), facts_len + 1);
+}
+
+#[test]
+fn simulate_retry_remove_validator() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_appchain_go_staging(&root, &oct, &relay);
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+    // The refund account is not registered in the OCT token contract yet.
+    let bob = root.create_user("bob".to_string(), to_yocto("100"));
+    alice
+        .call(
+            relay.account_id(),
+            "set_validator_refund_account",
+            &json!({
+                "appchain_id": "testchain",
+                "account": bob.valid_account_id()
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    let get_failed_removals = || -> Vec<FailedRemoval> {
+        root.view(
+            relay.account_id(),
+            "get_failed_removals",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json()
+    };
+    let get_validator = || -> Option<Validator> {
+        root.view(
+            relay.account_id(),
+            "get_validator",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json()
+    };
+
+    relay
+        .call(
+            relay.account_id(),
+            "remove_validator",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id1
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    assert!(get_validator().is_some());
+    assert_eq!(
+        get_failed_removals(),
+        vec![FailedRemoval {
+            validator_id: val_id1.to_string(),
+            refund_account: bob.account_id(),
+            amount: U128::from(to_yocto("200")),
+        }]
+    );
+
+    register_user(&bob);
+    relay
+        .call(
+            relay.account_id(),
+            "retry_remove_validator",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id1
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    assert!(get_validator().is_none());
+    assert!(get_failed_removals().is_empty());
+}
+
+#[test]
+fn simulate_disable_native_mint() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    lock_token(&b_token, &root, &relay, 100);
+    relay
+        .call(
+            relay.account_id(),
+            "register_native_token",
+            &json!({
+                "appchain_id": "testchain",
+                "token_id": alice.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    relay
+        .call(
+            relay.account_id(),
+            "set_bridge_directions",
+            &json!({
+                "appchain_id": "testchain",
+                "directions": {
+                    "allow_lock": true,
+                    "allow_mint_native": false,
+                    "allow_burn_native": true
+                }
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    let directions: BridgeDirections = root
+        .view(
+            relay.account_id(),
+            "get_bridge_directions",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    assert!(!directions.allow_mint_native);
+
+    let amount = to_decimals_amount(10, 12);
+    relay_messages(
+        &root,
+        &relay,
+        vec![
+            lock_message(&root, to_yocto("10"), 1),
+            burn_asset_message(&b_token, &alice, amount, 2),
+        ],
+    )
+    .assert_success();
+
+    // The mint is skipped and recorded, the unlock of bridge token still works.
+    let failed_mints: Vec<FailedMint> = root
+        .view(
+            relay.account_id(),
+            "get_failed_mints",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(failed_mints.len(), 1);
+    assert_eq!(failed_mints[0].nonce, 1);
+    let failed_unlocks: Vec<FailedUnlock> = root
+        .view(
+            relay.account_id(),
+            "get_failed_unlocks",
+            &json!({
+                "appchain_id": "testchain",
+                "start": 0,
+                "limit": 10
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert!(failed_unlocks.is_empty());
+    let message_used = is_message_used(&root, &relay, 2);
+    assert!(message_used);
+}
+
+#[test]
+fn simulate_mint_beyond_burnt_amount() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    relay
+        .call(
+            relay.account_id(),
+            "register_native_token",
+            &json!({
+                "appchain_id": "testchain",
+                "token_id": alice.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    let get_native_token_accounting = || -> (U128, U128) {
+        root.view(
+            relay.account_id(),
+            "get_native_token_accounting",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json()
+    };
+    let mint = |amount: u128, nonce: u64| -> ExecutionResult {
+        root.call(
+            relay.account_id(),
+            "mint_native_token",
+            &json!({
+                "appchain_id": "testchain",
+                "receiver_id": root.account_id(),
+                "amount": U128::from(amount),
+                "message_nonce": nonce,
+                "relayer": root.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            to_yocto("0.01"),
+        )
+    };
+
+    // Only the relay contract itself can mint, nothing is burnt on the appchain yet.
+    assert!(!mint(to_yocto("1"), 1).is_ok());
+
+    // The mint fails as the native token has no contract deployed, so it is reverted.
+    relay_messages(&root, &relay, vec![lock_message(&root, to_yocto("10"), 1)]).assert_success();
+    assert_eq!(
+        get_native_token_accounting(),
+        (U128::from(to_yocto("10")), U128::from(0))
+    );
+
+    // The burnt amount can't be minted without a relayed message either.
+    assert!(!mint(to_yocto("10"), 2).is_ok());
+    assert_eq!(
+        get_native_token_accounting(),
+        (U128::from(to_yocto("10")), U128::from(0))
+    );
+}
+
+#[test]
+fn simulate_founder_update_subql_url() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_register_appchain(&root, &oct, &relay);
+    let update_subql_url = |user: &UserAccount, subql_url: &str| -> ExecutionResult {
+        user.call(
+            relay.account_id(),
+            "update_subql_url",
+            &json!({
+                "appchain_id": "testchain",
+                "subql_url": subql_url
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+    let get_subql_url = || -> String {
+        let appchain: Option<Appchain> = root
+            .view(
+                relay.account_id(),
+                "get_appchain",
+                &json!({ "appchain_id": "testchain" })
+                    .to_string()
+                    .into_bytes(),
+            )
+            .unwrap_json();
+        appchain.unwrap().subql_url
+    };
+
+    update_subql_url(&root, "https://subql.founder.example").assert_success();
+    assert_eq!(get_subql_url(), "https://subql.founder.example");
+
+    assert!(!update_subql_url(&alice, "https://subql.alice.example").is_ok());
+    assert_eq!(get_subql_url(), "https://subql.founder.example");
+
+    update_subql_url(&relay, "https://subql.owner.example").assert_success();
+    assert_eq!(get_subql_url(), "https://subql.owner.example");
+}
+
+#[test]
+fn simulate_is_validator_set_rotation_due() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    let is_validator_set_rotation_due = || -> bool {
+        root.view(
+            relay.account_id(),
+            "is_validator_set_rotation_due",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json()
+    };
+    // The appchain is not registered yet.
+    assert!(!is_validator_set_rotation_due());
+
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    assert!(!is_validator_set_rotation_due());
+
+    default_stake_more(&root, &oct, &relay);
+    assert!(!is_validator_set_rotation_due());
+
+    // Wait for more than a cycle of validator set (20 minutes).
+    root.borrow_runtime_mut().produce_blocks(1300).unwrap();
+    assert!(is_validator_set_rotation_due());
+}
+
+#[test]
+fn simulate_register_appchain_without_github_info() {
+    let (root, oct, _, relay, alice) = default_init();
+    register_user(&relay);
+    root.call(
+        oct.account_id(),
+        "ft_transfer_call",
+        &json!({
+            "receiver_id": relay.valid_account_id(),
+            "amount": U128::from(to_yocto("200")),
+            "msg": "register_appchain,testchain,website_url_string,,,,email_string",
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        1,
+    )
+    .assert_success();
+    let get_appchain = || -> Appchain {
+        let appchain: Option<Appchain> = root
+            .view(
+                relay.account_id(),
+                "get_appchain",
+                &json!({ "appchain_id": "testchain" })
+                    .to_string()
+                    .into_bytes(),
+            )
+            .unwrap_json();
+        appchain.unwrap()
+    };
+    let appchain = get_appchain();
+    assert_eq!(appchain.website_url, "website_url_string");
+    assert_eq!(appchain.github_address, "");
+    assert_eq!(appchain.github_release, "");
+    assert_eq!(appchain.commit_id, "");
+
+    for method in ["pass_appchain", "appchain_go_staging"].iter() {
+        relay
+            .call(
+                relay.account_id(),
+                method,
+                &json!({ "appchain_id": "testchain" })
+                    .to_string()
+                    .into_bytes(),
+                DEFAULT_GAS,
+                0,
+            )
+            .assert_success();
+    }
+    default_stake(&root, &oct, &relay, val_id0);
+    default_stake(&alice, &oct, &relay, val_id1);
+    default_activate_appchain(&relay);
+
+    root.call(
+        relay.account_id(),
+        "update_appchain",
+        &json!({
+            "appchain_id": "testchain",
+            "website_url": "website_url_string",
+            "github_address": "github_address_string",
+            "github_release": "github_release_string",
+            "email": "email_string",
+            "rpc_endpoint": "rpc_endpoint"
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        0,
+    )
+    .assert_success();
+    let appchain = get_appchain();
+    assert_eq!(appchain.github_address, "github_address_string");
+    assert_eq!(appchain.github_release, "github_release_string");
+    assert_eq!(appchain.commit_id, "");
+}
+
+#[test]
+fn simulate_will_be_in_next_set() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    let will_be_in_next_set = |validator_id: &str| -> bool {
+        root.view(
+            relay.account_id(),
+            "will_be_in_next_set",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": validator_id
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json()
+    };
+    assert!(will_be_in_next_set(val_id0));
+    assert!(will_be_in_next_set(val_id1));
+    assert!(!will_be_in_next_set(
+        "0x0000000000000000000000000000000000000000000000000000000000000001"
+    ));
+
+    relay
+        .call(
+            relay.account_id(),
+            "set_validator_enabled",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id1,
+                "enabled": false
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    assert!(will_be_in_next_set(val_id0));
+    assert!(!will_be_in_next_set(val_id1));
+}
+
+#[test]
+fn simulate_max_receiver_len() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    register_user(&relay);
+    let lock_token_for = |receiver: &str| {
+        root.call(
+            b_token.account_id(),
+            "ft_transfer_call",
+            &json!({
+                "receiver_id": relay.valid_account_id(),
+                "amount": U128::from(to_decimals_amount(10, 12)),
+                "msg": format!("lock_token,testchain,{}", receiver),
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS / 2,
+            1,
+        )
+        .assert_success();
+    };
+
+    // The tokens are returned for the over-long receiver.
+    let balance_before = get_ft_balance(&b_token, &root);
+    lock_token_for(&"x".repeat(129));
+    assert_eq!(get_ft_balance(&b_token, &root), balance_before);
+    assert!(get_facts(&root, &relay).is_empty());
+
+    lock_token_for(&"x".repeat(128));
+    assert_eq!(
+        get_ft_balance(&b_token, &root).0,
+        balance_before.0 - to_decimals_amount(10, 12)
+    );
+    assert_eq!(get_facts(&root, &relay).len(), 1);
+}
+
+#[test]
+fn simulate_delegate() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    let bob = root.create_user("bob".to_string(), to_yocto("100"));
+    register_user(&bob);
+    root.call(
+        oct.account_id(),
+        "ft_transfer",
+        &json!({
+            "receiver_id": bob.valid_account_id(),
+            "amount": U128::from(to_yocto("200")),
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        1,
+    )
+    .assert_success();
+    let delegate = |validator_id: &str, amount: &str| {
+        bob.call(
+            oct.account_id(),
+            "ft_transfer_call",
+            &json!({
+                "receiver_id": relay.valid_account_id(),
+                "amount": U128::from(to_yocto(amount)),
+                "msg": format!("delegate,testchain,{}", validator_id),
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            1,
+        )
+        .assert_success();
+    };
+    let get_staked_balance = || -> U128 {
+        let appchain: Option<Appchain> = root
+            .view(
+                relay.account_id(),
+                "get_appchain",
+                &json!({ "appchain_id": "testchain" })
+                    .to_string()
+                    .into_bytes(),
+            )
+            .unwrap_json();
+        appchain.unwrap().staked_balance
+    };
+    let staked_balance = get_staked_balance().0;
+
+    // The tokens are returned if the validator doesn't exist.
+    delegate(
+        "0x0000000000000000000000000000000000000000000000000000000000000001",
+        "100",
+    );
+    assert_eq!(get_ft_balance(&oct, &bob), U128::from(to_yocto("200")));
+
+    // The tokens are returned if the amount is less than the minimum delegation amount.
+    let minimum_delegation_amount: U128 = root
+        .view(relay.account_id(), "get_minimum_delegation_amount", b"")
+        .unwrap_json();
+    assert_eq!(minimum_delegation_amount, U128::from(to_yocto("10")));
+    delegate(val_id0, "9");
+    assert_eq!(get_ft_balance(&oct, &bob), U128::from(to_yocto("200")));
+
+    delegate(val_id0, "100");
+    delegate(val_id0, "50");
+    assert_eq!(get_ft_balance(&oct, &bob), U128::from(to_yocto("50")));
+    let delegator: Option<Delegator> = root
+        .view(
+            relay.account_id(),
+            "get_delegator",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id0,
+                "delegator_id": bob.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    let delegator = delegator.unwrap();
+    assert_eq!(delegator.account_id, bob.account_id());
+    assert_eq!(delegator.amount, U128::from(to_yocto("150")));
+    assert_eq!(get_staked_balance().0, staked_balance + to_yocto("150"));
+    let total_staked_balance: U128 = root
+        .view(relay.account_id(), "get_total_staked_balance", b"")
+        .unwrap_json();
+    assert_eq!(total_staked_balance.0, staked_balance + to_yocto("150"));
+}
+
+#[test]
+fn simulate_get_delegators() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    let delegators: Vec<UserAccount> = ["bob", "carol"]
+        .iter()
+        .map(|name| {
+            let delegator = root.create_user(name.to_string(), to_yocto("100"));
+            register_user(&delegator);
+            root.call(
+                oct.account_id(),
+                "ft_transfer",
+                &json!({
+                    "receiver_id": delegator.valid_account_id(),
+                    "amount": U128::from(to_yocto("200")),
+                })
+                .to_string()
+                .into_bytes(),
+                DEFAULT_GAS,
+                1,
+            )
+            .assert_success();
+            delegator
+        })
+        .collect();
+    for (delegator, amount) in delegators.iter().zip(["50", "80"].iter()) {
+        delegator
+            .call(
+                oct.account_id(),
+                "ft_transfer_call",
+                &json!({
+                    "receiver_id": relay.valid_account_id(),
+                    "amount": U128::from(to_yocto(amount)),
+                    "msg": format!("delegate,testchain,{}", val_id0),
+                })
+                .to_string()
+                .into_bytes(),
+                DEFAULT_GAS,
+                1,
+            )
+            .assert_success();
+    }
+    let get_delegators = |from_index: u32, limit: u32| -> Vec<Delegator> {
+        root.view(
+            relay.account_id(),
+            "get_delegators",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id0,
+                "from_index": from_index,
+                "limit": limit
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json()
+    };
+
+    let num_delegators: u32 = root
+        .view(
+            relay.account_id(),
+            "get_num_delegators",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id0
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(num_delegators, 2);
+    let all_delegators = get_delegators(0, 10);
+    assert_eq!(
+        all_delegators
+            .iter()
+            .map(|d| (d.account_id.clone(), d.amount))
+            .collect::<Vec<(String, U128)>>(),
+        vec![
+            ("bob".to_string(), U128::from(to_yocto("50"))),
+            ("carol".to_string(), U128::from(to_yocto("80"))),
+        ]
+    );
+    assert_eq!(get_delegators(1, 10).len(), 1);
+    assert_eq!(get_delegators(1, 10)[0].account_id, "carol");
+    assert!(get_delegators(2, 10).is_empty());
+}
+
+#[test]
+fn simulate_undelegate() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    let bob = root.create_user("bob".to_string(), to_yocto("100"));
+    register_user(&bob);
+    root.call(
+        oct.account_id(),
+        "ft_transfer",
+        &json!({
+            "receiver_id": bob.valid_account_id(),
+            "amount": U128::from(to_yocto("200")),
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        1,
+    )
+    .assert_success();
+    let get_staked_balance = || -> U128 {
+        let appchain: Option<Appchain> = root
+            .view(
+                relay.account_id(),
+                "get_appchain",
+                &json!({ "appchain_id": "testchain" })
+                    .to_string()
+                    .into_bytes(),
+            )
+            .unwrap_json();
+        appchain.unwrap().staked_balance
+    };
+    let staked_balance = get_staked_balance();
+    bob.call(
+        oct.account_id(),
+        "ft_transfer_call",
+        &json!({
+            "receiver_id": relay.valid_account_id(),
+            "amount": U128::from(to_yocto("100")),
+            "msg": format!("delegate,testchain,{}", val_id0),
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        1,
+    )
+    .assert_success();
+    assert_eq!(get_staked_balance().0, staked_balance.0 + to_yocto("100"));
+
+    bob.call(
+        relay.account_id(),
+        "undelegate",
+        &json!({
+            "appchain_id": "testchain",
+            "validator_id": val_id0
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        0,
+    )
+    .assert_success();
+    let oct_balance = get_ft_balance(&oct, &bob);
+    assert_eq!(oct_balance, U128::from(to_yocto("200")));
+    let delegator: Option<Delegator> = root
+        .view(
+            relay.account_id(),
+            "get_delegator",
+            &json!({
+                "appchain_id": "testchain",
+                "validator_id": val_id0,
+                "delegator_id": bob.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert!(delegator.is_none());
+    assert_eq!(get_staked_balance(), staked_balance);
+}
+
+#[test]
+fn simulate_unstake_with_delegations() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    let bob = root.create_user("bob".to_string(), to_yocto("100"));
+    register_user(&bob);
+    root.call(
+        oct.account_id(),
+        "ft_transfer",
+        &json!({
+            "receiver_id": bob.valid_account_id(),
+            "amount": U128::from(to_yocto("200")),
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        1,
+    )
+    .assert_success();
+    bob.call(
+        oct.account_id(),
+        "ft_transfer_call",
+        &json!({
+            "receiver_id": relay.valid_account_id(),
+            "amount": U128::from(to_yocto("100")),
+            "msg": format!("delegate,testchain,{}", val_id0),
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        1,
+    )
+    .assert_success();
+    let get_unclaimed_balance = || -> U128 {
+        root.view(
+            relay.account_id(),
+            "get_unclaimed_balance",
+            &json!({
+                "appchain_id": "testchain",
+                "account_id": bob.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json()
+    };
+
+    // A delegation of a third party doesn't block the validator from unstaking.
+    root.call(
+        relay.account_id(),
+        "unstake",
+        &json!({ "appchain_id": "testchain" })
+            .to_string()
+            .into_bytes(),
+        DEFAULT_GAS,
+        0,
+    )
+    .assert_success();
+    assert_eq!(get_unclaimed_balance(), U128::from(to_yocto("100")));
+
+    bob.call(
+        relay.account_id(),
+        "withdraw_unclaimed_balance",
+        &json!({ "appchain_id": "testchain" })
+            .to_string()
+            .into_bytes(),
+        DEFAULT_GAS,
+        0,
+    )
+    .assert_success();
+    assert_eq!(get_unclaimed_balance(), U128::from(0));
+    let oct_balance = get_ft_balance(&oct, &bob);
+    assert_eq!(oct_balance, U128::from(to_yocto("200")));
+
+    // It can't be withdrawn twice.
+    let outcome = bob.call(
+        relay.account_id(),
+        "withdraw_unclaimed_balance",
+        &json!({ "appchain_id": "testchain" })
+            .to_string()
+            .into_bytes(),
+        DEFAULT_GAS,
+        0,
+    );
+    assert!(!outcome.is_ok());
+}
+
+#[test]
+fn simulate_reduce_stake() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    let reduce_stake = |amount: &str| -> ExecutionResult {
+        alice.call(
+            relay.account_id(),
+            "reduce_stake",
+            &json!({
+                "appchain_id": "testchain",
+                "amount": U128::from(to_yocto(amount))
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+    let get_staked_amount = || -> U128 {
+        let validator: Option<Validator> = root
+            .view(
+                relay.account_id(),
+                "get_validator",
+                &json!({
+                    "appchain_id": "testchain",
+                    "validator_id": val_id1
+                })
+                .to_string()
+                .into_bytes(),
+            )
+            .unwrap_json();
+        validator.unwrap().staked_amount
+    };
+    let oct_balance = get_ft_balance(&oct, &alice).0;
+
+    // The remaining amount would be less than the minimum staking amount.
+    assert!(!reduce_stake("150").is_ok());
+    assert_eq!(get_staked_amount(), U128::from(to_yocto("200")));
+    assert_eq!(get_ft_balance(&oct, &alice).0, oct_balance);
+
+    reduce_stake("100").assert_success();
+    assert_eq!(get_staked_amount(), U128::from(to_yocto("100")));
+    assert_eq!(
+        get_ft_balance(&oct, &alice).0,
+        oct_balance + to_yocto("100")
+    );
+}
+
+#[test]
+fn simulate_relay_forged_messages() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    lock_token(&b_token, &root, &relay, 100);
+
+    let messages = vec![burn_asset_message(
+        &b_token,
+        &alice,
+        to_decimals_amount(10, 12),
+        1,
+    )];
+    let (header_partial, leaf_proof, mmr_root) = prove_messages(&relay, &messages.encode());
+    let forged_messages = vec![burn_asset_message(
+        &b_token,
+        &alice,
+        to_decimals_amount(90, 12),
+        1,
+    )];
+    let relay_with_root = |encoded_messages: Vec<u8>, mmr_root: Vec<u8>| -> ExecutionResult {
+        root.call(
+            relay.account_id(),
+            "relay",
+            &json!({
+                "appchain_id": "testchain",
+                "encoded_messages": encoded_messages,
+                "header_partial": header_partial,
+                "leaf_proof": leaf_proof,
+                "mmr_root": mmr_root,
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+
+    assert!(!relay_with_root(forged_messages.encode(), mmr_root.clone()).is_ok());
+    // The root passed by the caller is not trusted.
+    let (_, _, forged_root) = single_leaf_proof(&forged_messages.encode());
+    assert!(!relay_with_root(forged_messages.encode(), forged_root).is_ok());
+    // The messages can't be executed without being relayed.
+    let outcome = root.call(
+        relay.account_id(),
+        "execute",
+        &json!({
+            "messages": [{
+                "nonce": 1,
+                "payload": {
+                    "BurnAsset": {
+                        "token_id": b_token.account_id(),
+                        "sender": "0x0000000000000000000000000000000000000000",
+                        "receiver_id": alice.account_id(),
+                        "amount": U128::from(to_decimals_amount(90, 12)),
+                    }
+                }
+            }],
+            "appchain_id": "testchain",
+            "remaining_deposit": 0,
+            "relayer": root.account_id(),
+        })
+        .to_string()
+        .into_bytes(),
+        DEFAULT_GAS,
+        0,
+    );
+    assert!(!outcome.is_ok());
+    relay_with_root(messages.encode(), mmr_root).assert_success();
+}
+
+#[test]
+fn simulate_replay_message() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    lock_token(&b_token, &root, &relay, 100);
+
+    let message = burn_asset_message(&b_token, &alice, to_decimals_amount(10, 12), 1);
+    let outcome = relay_messages(&root, &relay, vec![message.clone(), message.clone()]);
+    assert!(!outcome.is_ok());
+
+    let balance = get_ft_balance(&b_token, &alice);
+    relay_messages(&root, &relay, vec![message.clone()]).assert_success();
+    assert_eq!(
+        get_ft_balance(&b_token, &alice).0,
+        balance.0 + to_decimals_amount(10, 12)
+    );
+
+    let outcome = relay_messages(&root, &relay, vec![message]);
+    assert!(!outcome.is_ok());
+    assert_eq!(
+        get_ft_balance(&b_token, &alice).0,
+        balance.0 + to_decimals_amount(10, 12)
+    );
+}
+
+#[test]
+fn simulate_vote() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_pass_appchain(&root, &oct, &relay);
+    let get_appchain_votes = || -> (U128, U128) {
+        root.view(
+            relay.account_id(),
+            "get_appchain_votes",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json()
+    };
+    let alice_balance = get_ft_balance(&oct, &alice);
+
+    vote(&alice, &oct, &relay, "yes", to_yocto("100")).assert_success();
+    vote(&alice, &oct, &relay, "no", to_yocto("10")).assert_success();
+    vote(&root, &oct, &relay, "no", to_yocto("50")).assert_success();
+    assert_eq!(
+        get_appchain_votes(),
+        (U128::from(to_yocto("100")), U128::from(to_yocto("60")))
+    );
+    let account_vote: Option<AccountVote> = root
+        .view(
+            relay.account_id(),
+            "get_account_vote",
+            &json!({
+                "appchain_id": "testchain",
+                "account_id": alice.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(
+        account_vote,
+        Some(AccountVote {
+            upvote: U128::from(to_yocto("100")),
+            downvote: U128::from(to_yocto("10")),
+        })
+    );
+    // The tokens are held by the contract.
+    assert_eq!(
+        get_ft_balance(&oct, &alice).0,
+        alice_balance.0 - to_yocto("110")
+    );
+
+    // Votes are only accepted while the appchain is in voting.
+    relay
+        .call(
+            relay.account_id(),
+            "appchain_go_staging",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    vote(&alice, &oct, &relay, "yes", to_yocto("100"));
+    assert_eq!(
+        get_ft_balance(&oct, &alice).0,
+        alice_balance.0 - to_yocto("110")
+    );
+    assert_eq!(
+        get_appchain_votes(),
+        (U128::from(to_yocto("100")), U128::from(to_yocto("60")))
+    );
+}
+
+#[test]
+fn simulate_withdraw_vote() {
+    let (root, oct, _, relay, alice) = default_init();
+    default_pass_appchain(&root, &oct, &relay);
+    let withdraw_vote = || -> ExecutionResult {
+        alice.call(
+            relay.account_id(),
+            "withdraw_vote",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+    let balance = get_ft_balance(&oct, &alice);
+    vote(&alice, &oct, &relay, "yes", to_yocto("100")).assert_success();
+    vote(&alice, &oct, &relay, "no", to_yocto("10")).assert_success();
+    vote(&root, &oct, &relay, "no", to_yocto("50")).assert_success();
+
+    assert!(!withdraw_vote().is_ok());
+    assert_eq!(get_ft_balance(&oct, &alice).0, balance.0 - to_yocto("110"));
+
+    relay
+        .call(
+            relay.account_id(),
+            "appchain_go_staging",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    withdraw_vote().assert_success();
+    assert_eq!(get_ft_balance(&oct, &alice), balance);
+    let account_vote: Option<AccountVote> = root
+        .view(
+            relay.account_id(),
+            "get_account_vote",
+            &json!({
+                "appchain_id": "testchain",
+                "account_id": alice.account_id()
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json();
+    assert!(account_vote.is_none());
+    let votes: (U128, U128) = root
+        .view(
+            relay.account_id(),
+            "get_appchain_votes",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(votes, (U128::from(0), U128::from(to_yocto("50"))));
+
+    assert!(!withdraw_vote().is_ok());
+    assert_eq!(get_ft_balance(&oct, &alice), balance);
+}
+
+#[test]
+fn simulate_freeze_appchain() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    relay
+        .call(
+            relay.account_id(),
+            "freeze_appchain",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+
+    let appchain: Option<Appchain> = root
+        .view(
+            relay.account_id(),
+            "get_appchain",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(appchain.unwrap().status, AppchainStatus::Frozen);
+    // No staking to a frozen appchain, the tokens are returned.
+    let balance = get_ft_balance(&oct, &alice);
+    default_stake_more(&alice, &oct, &relay);
+    assert_eq!(get_ft_balance(&oct, &alice), balance);
+}
+
+#[test]
+fn simulate_unfreeze_appchain() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    let call_relay = |method_name: &str| -> ExecutionResult {
+        relay.call(
+            relay.account_id(),
+            method_name,
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+    let facts_len = get_facts(&root, &relay).len();
+
+    call_relay("freeze_appchain").assert_success();
+    // The tokens are returned, no fact of locking is recorded.
+    assert_eq!(lock_token(&b_token, &root, &relay, 100).len(), facts_len);
+
+    call_relay("unfreeze_appchain").assert_success();
+    assert!(!call_relay("unfreeze_appchain").is_ok());
+    let appchain: Option<Appchain> = root
+        .view(
+            relay.account_id(),
+            "get_appchain",
+            &json!({ "appchain_id": "testchain" })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(appchain.unwrap().status, AppchainStatus::Booting);
+    assert_eq!(
+        lock_token(&b_token, &root, &relay, 100).len(),
+        facts_len + 1
+    );
+}
+
+#[test]
+fn simulate_close_and_unregister_bridge_token() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    default_set_bridge_permitted(&b_token, &relay, true);
+    let facts_len = lock_token(&b_token, &root, &relay, 100).len();
+    let call_relay = |method_name: &str, token_id: &str| -> ExecutionResult {
+        relay.call(
+            relay.account_id(),
+            method_name,
+            &json!({ "token_id": token_id }).to_string().into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+
+    call_relay("close_bridge_token", b_token.account_id().as_str()).assert_success();
+    assert!(!call_relay("close_bridge_token", b_token.account_id().as_str()).is_ok());
+    let bridge_token: Option<BridgeToken> = root
+        .view(
+            relay.account_id(),
+            "get_bridge_token",
+            &json!({ "token_id": b_token.valid_account_id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .unwrap_json();
+    assert_eq!(bridge_token.unwrap().status, BridgeStatus::Closed);
+    let bridge_allowed = get_bridge_allowed(&root, &relay, &b_token);
+    assert_eq!(bridge_allowed, U128::from(0));
+    // The tokens are returned, no fact of locking is recorded.
+    assert_eq!(lock_token(&b_token, &root, &relay, 100).len(), facts_len);
+
+    // The token is still locked in the appchain.
+    assert!(!call_relay("unregister_bridge_token", b_token.account_id().as_str()).is_ok());
+
+    relay
+        .call(
+            relay.account_id(),
+            "register_bridge_token",
+            &json!({
+                "token_id": "unused_token",
+                "symbol": "UNT",
+                "price": U128::from(1000000),
+                "decimals": 12,
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    call_relay("unregister_bridge_token", "unused_token").assert_success();
+    assert!(root
+        .view(
+            relay.account_id(),
+            "get_bridge_token",
+            &json!({ "token_id": "unused_token" })
+                .to_string()
+                .into_bytes(),
+        )
+        .is_err());
+}
+
+#[test]
+fn simulate_get_bridge_tokens() {
+    let (root, oct, b_token, relay, alice) = default_init();
+    default_register_bridge_token(&root, &oct, &b_token, &relay, &alice);
+    relay
+        .call(
+            relay.account_id(),
+            "register_bridge_token",
+            &json!({
+                "token_id": "another_token",
+                "symbol": "ATK",
+                "price": U128::from(2000000),
+                "decimals": 6,
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    let get_bridge_tokens = |from_index: u32, limit: u32| -> Vec<BridgeToken> {
+        root.view(
+            relay.account_id(),
+            "get_bridge_tokens",
+            &json!({
+                "from_index": from_index,
+                "limit": limit
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .unwrap_json()
+    };
+
+    let num_bridge_tokens: u32 = root
+        .view(relay.account_id(), "get_num_bridge_tokens", b"")
+        .unwrap_json();
+    assert_eq!(num_bridge_tokens, 2);
+    let bridge_tokens = get_bridge_tokens(0, 10);
+    assert_eq!(bridge_tokens.len(), 2);
+    assert_eq!(bridge_tokens[0].token_id, b_token.account_id());
+    assert_eq!(bridge_tokens[1].token_id, "another_token");
+    assert_eq!(bridge_tokens[1].decimals, 6);
+    let bridge_tokens = get_bridge_tokens(1, u32::MAX);
+    assert_eq!(bridge_tokens.len(), 1);
+    assert_eq!(bridge_tokens[0].token_id, "another_token");
+    assert!(get_bridge_tokens(2, 10).is_empty());
+}
+
+#[test]
+fn simulate_get_appchains_by_status() {
+    let (root, oct, _, relay, _) = default_init();
+    default_pass_appchain(&root, &oct, &relay);
+    for appchain_id in ["chain1", "chain2"].iter() {
+        root.call(
+            oct.account_id(),
+            "ft_transfer_call",
+            &json!({
+                "receiver_id": relay.valid_account_id(),
+                "amount": U128::from(to_yocto("200")),
+                "msg": format!("register_appchain,{}", appchain_id),
+            })
+            .to_string()
+            .into_bytes(),
+            DEFAULT_GAS,
+            1,
+        )
+        .assert_success();
+    }
+    relay
+        .call(
+            relay.account_id(),
+            "pass_appchain",
+            &json!({ "appchain_id": "chain2" }).to_string().into_bytes(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    let get_appchain_ids = |status: AppchainStatus, from_index: u32, limit: u32| -> Vec<String> {
+        let appchains: Vec<Appchain> = root
+            .view(
+                relay.account_id(),
+                "get_appchains_by_status",
+                &json!({
+                    "status": status,
+                    "from_index": from_index,
+                    "limit": limit
+                })
+                .to_string()
+                .into_bytes(),
+            )
+            .unwrap_json();
+        appchains.into_iter().map(|appchain| appchain.id).collect()
+    };
+
+    assert_eq!(
+        get_appchain_ids(AppchainStatus::Voting, 0, 10),
+        vec!["testchain", "chain2"]
+    );
+    assert_eq!(
+        get_appchain_ids(AppchainStatus::Voting, 1, 10),
+        vec!["chain2"]
+    );
+    assert_eq!(
+        get_appchain_ids(AppchainStatus::Voting, 0, 1),
+        vec!["testchain"]
+    );
+    assert_eq!(
+        get_appchain_ids(AppchainStatus::Auditing, 0, 10),
+        vec!["chain1"]
+    );
+    assert!(get_appchain_ids(AppchainStatus::Booting, 0, 10).is_empty());
+}
@@ -279,11 +279,7 @@ pub fn upgrade_contract_code_and_perform_migration(relay: &near_sdk_sim::UserAcc
     let result = relay.call(
         RELAY_ID.into(),
         "migrate_state",
-        &json!({
-            "new_note_of_validator": "migrate to new version",
-        })
-        .to_string()
-        .into_bytes(),
+        &json!({}).to_string().into_bytes(),
         DEFAULT_GAS / 2,
         0, // attached deposit
     );